@@ -0,0 +1,535 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use libc::c_int;
+use libmdb_sys::{
+    MDB_BINARY, MDB_BOOL, MDB_BYTE, MDB_COMPLEX, MDB_DATABASE_PROPERTY, MDB_DATETIME, MDB_DOUBLE,
+    MDB_FLOAT, MDB_FORM, MDB_INT, MDB_LINKED_TABLE, MDB_LONGINT, MDB_MACRO, MDB_MEMO, MDB_MODULE,
+    MDB_MONEY, MDB_NUMERIC, MDB_OLE, MDB_QUERY, MDB_RELATIONSHIP, MDB_REPID, MDB_REPORT,
+    MDB_SYSTEM_TABLE, MDB_TABLE, MDB_TEXT,
+};
+
+use crate::error::Error;
+use crate::mdbsql::Rows;
+
+/// `grbit` flags of `MSysRelationships`.
+const RELATIONSHIP_ONE_TO_ONE: i32 = 0x1;
+const RELATIONSHIP_NO_INTEGRITY: i32 = 0x2;
+const RELATIONSHIP_CASCADE_UPDATES: i32 = 0x100;
+const RELATIONSHIP_CASCADE_DELETES: i32 = 0x1000;
+
+/// Type of an object in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+    /// Form.
+    Form,
+    /// Table.
+    Table,
+    /// Macro.
+    Macro,
+    /// System table.
+    SystemTable,
+    /// Report.
+    Report,
+    /// Saved query.
+    Query,
+    /// Linked table.
+    LinkedTable,
+    /// VBA module.
+    Module,
+    /// Relationship.
+    Relationship,
+    /// Database properties.
+    DatabaseProperty,
+    /// Type code without a known meaning.
+    Unknown(i32),
+}
+
+impl From<c_int> for ObjectType {
+    fn from(object_type: c_int) -> Self {
+        match object_type {
+            t if t == MDB_FORM as c_int => ObjectType::Form,
+            t if t == MDB_TABLE as c_int => ObjectType::Table,
+            t if t == MDB_MACRO as c_int => ObjectType::Macro,
+            t if t == MDB_SYSTEM_TABLE as c_int => ObjectType::SystemTable,
+            t if t == MDB_REPORT as c_int => ObjectType::Report,
+            t if t == MDB_QUERY as c_int => ObjectType::Query,
+            t if t == MDB_LINKED_TABLE as c_int => ObjectType::LinkedTable,
+            t if t == MDB_MODULE as c_int => ObjectType::Module,
+            t if t == MDB_RELATIONSHIP as c_int => ObjectType::Relationship,
+            t if t == MDB_DATABASE_PROPERTY as c_int => ObjectType::DatabaseProperty,
+            t => ObjectType::Unknown(t),
+        }
+    }
+}
+
+/// Object listed in the catalog of a database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogEntry {
+    /// Name of the object.
+    pub name: String,
+    /// Type of the object.
+    pub object_type: ObjectType,
+    /// Id of the object in `MSysObjects` without its high byte, i.e. the
+    /// definition page of tables.
+    pub id: u32,
+    /// Flags of the object, as stored in `MSysObjects`.
+    pub flags: i32,
+    /// Whether the object is a system table.
+    pub system: bool,
+    /// Source of a linked table.
+    pub link: Option<Link>,
+}
+
+/// Source of a linked table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Path of the database holding the table, as stored by Access.
+    pub database: String,
+    /// Name of the table in that database.
+    pub foreign_name: String,
+}
+
+impl Link {
+    /// Path of the linked database, relative to `dir`, the directory of the
+    /// database linking to it.
+    ///
+    /// Windows paths are reduced to their file name, looked up in `dir`.
+    pub fn resolve(&self, dir: &Path) -> PathBuf {
+        let windows =
+            self.database.contains('\\') || self.database.as_bytes().get(1) == Some(&b':');
+        if windows {
+            let file_name = self.database.rsplit(['\\', '/']).next().unwrap_or_default();
+            dir.join(file_name)
+        } else {
+            dir.join(&self.database)
+        }
+    }
+}
+
+/// Read the sources of linked tables, by table name, from the rows of
+/// `MSysObjects`.
+pub(crate) fn links(rows: Rows) -> Result<HashMap<String, Link>, Error> {
+    rows.map(|row| {
+        let link = Link {
+            database: row.get("Database")?,
+            foreign_name: row.get("ForeignName")?,
+        };
+        Ok((row.get("Name")?, link))
+    })
+    .collect()
+}
+
+/// Access type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnType {
+    /// Yes/No.
+    Bool,
+    /// Byte.
+    Byte,
+    /// Integer.
+    Int,
+    /// Long Integer.
+    LongInt,
+    /// Currency.
+    Money,
+    /// Single.
+    Float,
+    /// Double.
+    Double,
+    /// Date/Time.
+    DateTime,
+    /// Binary.
+    Binary,
+    /// Text.
+    Text,
+    /// OLE Object.
+    Ole,
+    /// Memo.
+    Memo,
+    /// Replication ID.
+    RepId,
+    /// Decimal.
+    Numeric,
+    /// Attachment or multi-valued field.
+    Complex,
+    /// Type code without a known meaning.
+    Unknown(i32),
+}
+
+impl From<c_int> for ColumnType {
+    fn from(col_type: c_int) -> Self {
+        match col_type as u32 {
+            MDB_BOOL => ColumnType::Bool,
+            MDB_BYTE => ColumnType::Byte,
+            MDB_INT => ColumnType::Int,
+            MDB_LONGINT => ColumnType::LongInt,
+            MDB_MONEY => ColumnType::Money,
+            MDB_FLOAT => ColumnType::Float,
+            MDB_DOUBLE => ColumnType::Double,
+            MDB_DATETIME => ColumnType::DateTime,
+            MDB_BINARY => ColumnType::Binary,
+            MDB_TEXT => ColumnType::Text,
+            MDB_OLE => ColumnType::Ole,
+            MDB_MEMO => ColumnType::Memo,
+            MDB_REPID => ColumnType::RepId,
+            MDB_NUMERIC => ColumnType::Numeric,
+            MDB_COMPLEX => ColumnType::Complex,
+            _ => ColumnType::Unknown(col_type),
+        }
+    }
+}
+
+/// Definition of a table column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// Name of the column.
+    pub name: String,
+    /// Access type of the column.
+    pub column_type: ColumnType,
+    /// Size of the column in bytes, e.g. the maximum length of Text.
+    pub size: i32,
+    /// Total number of digits of Decimal columns.
+    pub precision: u8,
+    /// Number of digits after the decimal point of Decimal columns.
+    pub scale: u8,
+    /// Whether values are stored with a fixed length.
+    pub fixed: bool,
+    /// Whether the column accepts NULL, i.e. it is not required.
+    pub nullable: bool,
+    /// Whether values are an autonumber.
+    pub auto_number: bool,
+    /// Whether values are an autonumber Replication ID.
+    pub auto_guid: bool,
+}
+
+/// Sort order of an index column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Ascending.
+    Ascending,
+    /// Descending.
+    Descending,
+}
+
+/// Column of an index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexColumn {
+    /// Name of the column.
+    pub name: String,
+    /// Sort order of the column in the index.
+    pub order: SortOrder,
+}
+
+/// Definition of a table index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    /// Name of the index.
+    pub name: String,
+    /// Key columns, in order.
+    pub columns: Vec<IndexColumn>,
+    /// Whether the index is the primary key.
+    pub primary_key: bool,
+    /// Whether the index backs the foreign key of a relationship.
+    pub foreign_key: bool,
+    /// Whether keys are unique.
+    pub unique: bool,
+    /// Whether rows with NULL keys are left out of the index.
+    pub ignore_nulls: bool,
+    /// Whether keys are required.
+    pub required: bool,
+}
+
+/// Properties of a table or column, set in Access.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Properties(BTreeMap<String, String>);
+
+impl Properties {
+    pub(crate) fn new(properties: BTreeMap<String, String>) -> Self {
+        Self(properties)
+    }
+
+    /// Value of a property by name, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// All properties, by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// `Description` property.
+    pub fn description(&self) -> Option<&str> {
+        self.get("Description")
+    }
+
+    /// `Caption` property.
+    pub fn caption(&self) -> Option<&str> {
+        self.get("Caption")
+    }
+
+    /// `Format` property.
+    pub fn format(&self) -> Option<&str> {
+        self.get("Format")
+    }
+
+    /// `DefaultValue` property.
+    pub fn default_value(&self) -> Option<&str> {
+        self.get("DefaultValue")
+    }
+
+    /// `ValidationRule` property.
+    pub fn validation_rule(&self) -> Option<&str> {
+        self.get("ValidationRule")
+    }
+
+    /// `ValidationText` property.
+    pub fn validation_text(&self) -> Option<&str> {
+        self.get("ValidationText")
+    }
+
+    /// `InputMask` property.
+    pub fn input_mask(&self) -> Option<&str> {
+        self.get("InputMask")
+    }
+
+    /// Whether a value is required, i.e. the column is NOT NULL.
+    pub fn required(&self) -> bool {
+        self.get("Required").is_some_and(|v| v.starts_with('y'))
+    }
+}
+
+/// Row count and storage of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStats {
+    /// Name of the table.
+    pub name: String,
+    /// Number of rows, as recorded in the table definition.
+    pub num_rows: u32,
+    /// Number of data pages holding rows of the table.
+    pub data_pages: u32,
+    /// Number of pages holding indexes of the table.
+    pub index_pages: u32,
+    /// Approximate size in bytes, i.e. the size of its data and index pages.
+    ///
+    /// Memo and OLE values stored on pages of their own are not counted.
+    pub size: u64,
+}
+
+/// Storage summary of a database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseStats {
+    /// Size of a page in bytes, 2048 for Jet 3 and 4096 for later versions.
+    pub page_size: u32,
+    /// Number of pages in the file.
+    pub pages: u32,
+    /// Number of data pages, including pages of Memo and OLE values.
+    pub data_pages: u32,
+    /// Number of table definition pages.
+    pub definition_pages: u32,
+    /// Number of index pages.
+    pub index_pages: u32,
+    /// Number of page usage map pages.
+    pub usage_map_pages: u32,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Statistics of user tables.
+    pub tables: Vec<TableStats>,
+}
+
+/// Relationship between two tables, i.e. a foreign key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    /// Name of the relationship.
+    pub name: String,
+    /// Table holding the foreign key.
+    pub table: String,
+    /// Table referenced by the foreign key.
+    pub referenced_table: String,
+    /// Pairs of foreign key column and referenced column, in order.
+    pub columns: Vec<(String, String)>,
+    /// Whether each referenced row has at most one referencing row.
+    pub one_to_one: bool,
+    /// Whether referential integrity is enforced.
+    pub enforced: bool,
+    /// Whether updates of referenced keys cascade.
+    pub cascade_update: bool,
+    /// Whether deletes of referenced rows cascade.
+    pub cascade_delete: bool,
+}
+
+/// Read relationships from the rows of `MSysRelationships`, which hold one
+/// row per column pair.
+pub(crate) fn relationships(rows: Rows) -> Result<Vec<Relationship>, Error> {
+    // column pairs with their position
+    type Columns = Vec<(i32, String, String)>;
+    let mut relationships: Vec<(Relationship, Columns)> = vec![];
+
+    for row in rows {
+        let name: String = row.get("szRelationship")?;
+        let column = (
+            row.get("icolumn")?,
+            row.get("szColumn")?,
+            row.get("szReferencedColumn")?,
+        );
+
+        match relationships.iter_mut().find(|(r, _)| r.name == name) {
+            Some((_, columns)) => columns.push(column),
+            None => {
+                let grbit: i32 = row.get("grbit")?;
+                let relationship = Relationship {
+                    name,
+                    table: row.get("szObject")?,
+                    referenced_table: row.get("szReferencedObject")?,
+                    columns: vec![],
+                    one_to_one: grbit & RELATIONSHIP_ONE_TO_ONE != 0,
+                    enforced: grbit & RELATIONSHIP_NO_INTEGRITY == 0,
+                    cascade_update: grbit & RELATIONSHIP_CASCADE_UPDATES != 0,
+                    cascade_delete: grbit & RELATIONSHIP_CASCADE_DELETES != 0,
+                };
+                relationships.push((relationship, vec![column]));
+            }
+        }
+    }
+
+    Ok(relationships
+        .into_iter()
+        .map(|(mut relationship, mut columns)| {
+            columns.sort_by_key(|(i, _, _)| *i);
+            relationship.columns = columns.into_iter().map(|(_, c, r)| (c, r)).collect();
+            relationship
+        })
+        .collect())
+}
+
+/// Order `tables` so that each table comes after the tables it references,
+/// e.g. to load them into a database enforcing foreign keys.
+///
+/// Tables keep their given order where they do not depend on each other.
+/// Self references are ignored, and other cycles are an error.
+pub fn dependency_order(
+    tables: &[String],
+    relationships: &[Relationship],
+) -> Result<Vec<String>, Error> {
+    let known: HashSet<&str> = tables.iter().map(|t| t.as_str()).collect();
+    let mut dependencies: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for r in relationships {
+        if r.table != r.referenced_table
+            && known.contains(r.table.as_str())
+            && known.contains(r.referenced_table.as_str())
+        {
+            dependencies
+                .entry(r.table.as_str())
+                .or_default()
+                .insert(r.referenced_table.as_str());
+        }
+    }
+
+    let mut ordered: Vec<String> = Vec::with_capacity(tables.len());
+    let mut done: HashSet<&str> = HashSet::new();
+    while ordered.len() < known.len() {
+        let ready: Vec<&str> = tables
+            .iter()
+            .map(|t| t.as_str())
+            .filter(|t| !done.contains(t))
+            .filter(|t| {
+                !dependencies
+                    .get(t)
+                    .is_some_and(|deps| deps.iter().any(|d| !done.contains(d)))
+            })
+            .collect();
+
+        if ready.is_empty() {
+            let cycle = tables
+                .iter()
+                .filter(|t| !done.contains(t.as_str()))
+                .cloned()
+                .collect();
+            return Err(Error::DependencyCycle(cycle));
+        }
+        for table in ready {
+            if done.insert(table) {
+                ordered.push(table.to_string());
+            }
+        }
+    }
+
+    Ok(ordered)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn resolve_link() {
+        let dir = Path::new("/srv/app");
+        let link = |database: &str| Link {
+            database: database.to_string(),
+            foreign_name: "Orders".to_string(),
+        };
+
+        assert_eq!(
+            link("C:\\Data\\Backend.mdb").resolve(dir),
+            Path::new("/srv/app/Backend.mdb")
+        );
+        assert_eq!(
+            link("\\\\server\\share\\Backend.mdb").resolve(dir),
+            Path::new("/srv/app/Backend.mdb")
+        );
+        assert_eq!(
+            link("data/Backend.mdb").resolve(dir),
+            Path::new("/srv/app/data/Backend.mdb")
+        );
+        assert_eq!(
+            link("/var/db/Backend.mdb").resolve(dir),
+            Path::new("/var/db/Backend.mdb")
+        );
+    }
+
+    fn relationship(table: &str, referenced_table: &str) -> Relationship {
+        Relationship {
+            name: format!("{}{}", referenced_table, table),
+            table: table.to_string(),
+            referenced_table: referenced_table.to_string(),
+            columns: vec![],
+            one_to_one: false,
+            enforced: true,
+            cascade_update: false,
+            cascade_delete: false,
+        }
+    }
+
+    #[test]
+    fn dependency_order() {
+        let tables: Vec<String> = ["Lines", "Orders", "Products", "Customers"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let relationships = vec![
+            relationship("Lines", "Orders"),
+            relationship("Lines", "Products"),
+            relationship("Orders", "Customers"),
+            relationship("Customers", "Customers"),
+            relationship("Orders", "Missing"),
+        ];
+
+        assert_eq!(
+            super::dependency_order(&tables, &relationships).unwrap(),
+            vec!["Products", "Customers", "Orders", "Lines"]
+        );
+
+        let relationships = vec![
+            relationship("Orders", "Lines"),
+            relationship("Lines", "Orders"),
+        ];
+        assert!(matches!(
+            super::dependency_order(&tables, &relationships),
+            Err(Error::DependencyCycle(cycle)) if cycle == vec!["Lines", "Orders"]
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+use crate::error::Error;
+use crate::params::{quote, ToSql};
+use crate::value::{DateTime, Value};
+
+impl TryFrom<&Value> for NaiveDateTime {
+    type Error = Error;
+
+    /// Converts Date/Time values from their days since 1899-12-30.
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        let dt = DateTime::try_from(value)?;
+        let (hour, minute, second, milli) = dt.time();
+        i32::try_from(dt.year)
+            .ok()
+            .and_then(|year| NaiveDate::from_ymd_opt(year, dt.month, dt.day))
+            .and_then(|date| date.and_hms_milli_opt(hour, minute, second, milli))
+            .ok_or_else(|| Error::InvalidDateTime(dt.iso()))
+    }
+}
+
+impl TryFrom<&Value> for NaiveDate {
+    type Error = Error;
+
+    /// Converts the date of Date/Time values.
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        NaiveDateTime::try_from(value).map(|dt| dt.date())
+    }
+}
+
+impl ToSql for NaiveDateTime {
+    fn to_sql(&self) -> Result<String, Error> {
+        Ok(quote(&format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second()
+        )))
+    }
+}
+
+impl ToSql for NaiveDate {
+    fn to_sql(&self) -> Result<String, Error> {
+        self.and_time(NaiveTime::MIN).to_sql()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Connection;
+
+    #[test]
+    fn datetime() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let dates: Vec<(NaiveDateTime, NaiveDate, Option<NaiveDateTime>)> = conn
+            .prepare("select D from Table1")
+            .unwrap()
+            .map(|r| Ok((r.get(0)?, r.get(0)?, r.get(0)?)))
+            .collect::<Result<_, Error>>()
+            .unwrap();
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            dates,
+            vec![
+                (
+                    date(2000, 1, 1).and_hms_opt(0, 0, 0).unwrap(),
+                    date(2000, 1, 1),
+                    date(2000, 1, 1).and_hms_opt(0, 0, 0)
+                ),
+                (
+                    date(1999, 1, 1).and_hms_opt(0, 0, 0).unwrap(),
+                    date(1999, 1, 1),
+                    date(1999, 1, 1).and_hms_opt(0, 0, 0)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from() {
+        let value = Value::DateTime(36526.75);
+        assert_eq!(
+            NaiveDateTime::try_from(&value).unwrap(),
+            NaiveDate::from_ymd_opt(2000, 1, 1)
+                .unwrap()
+                .and_hms_opt(18, 0, 0)
+                .unwrap()
+        );
+        assert!(NaiveDate::try_from(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn to_sql() {
+        let date = NaiveDate::from_ymd_opt(2000, 1, 2).unwrap();
+        assert_eq!(date.to_sql().unwrap(), "'2000-01-02 00:00:00'");
+        assert_eq!(
+            date.and_hms_opt(3, 4, 5).unwrap().to_sql().unwrap(),
+            "'2000-01-02 03:04:05'"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use serde::de::{
+    self,
+    value::{SeqDeserializer, StringDeserializer},
+    DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use crate::error::Error;
+use crate::mdbsql::Row;
+use crate::value::{self, DateTime, GuidFormat, Value, ValueRef};
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::DeserializeError(msg.to_string())
+    }
+}
+
+/// Deserializer of a row into a struct, a map or a sequence.
+pub(crate) struct RowDeserializer<'a> {
+    row: &'a Row,
+}
+
+impl<'a> RowDeserializer<'a> {
+    pub(crate) fn new(row: &'a Row) -> Self {
+        Self { row }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for RowDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let keys = self.row.columns().iter().map(|c| c.name()).collect();
+        visitor.visit_map(RowAccess::new(self.row, keys))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // Match column names to fields ignoring case, like Access does.
+        let keys = self
+            .row
+            .columns()
+            .iter()
+            .map(|c| {
+                let name = c.name();
+                fields
+                    .iter()
+                    .find(|f| f.eq_ignore_ascii_case(&name))
+                    .map_or(name, |f| f.to_string())
+            })
+            .collect();
+        visitor.visit_map(RowAccess::new(self.row, keys))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(RowAccess::new(self.row, vec![]))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct enum identifier ignored_any
+    }
+}
+
+/// Access to the cells of a row, keyed by `keys` when used as a map.
+struct RowAccess<'a> {
+    row: &'a Row,
+    keys: Vec<String>,
+    idx: usize,
+}
+
+impl<'a> RowAccess<'a> {
+    fn new(row: &'a Row, keys: Vec<String>) -> Self {
+        Self { row, keys, idx: 0 }
+    }
+
+    fn next_value<'de, T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, Error> {
+        let value = self.row.get_ref(self.idx)?;
+        self.idx += 1;
+        seed.deserialize(ValueDeserializer::new(value))
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for RowAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.keys.get(self.idx) {
+            Some(key) => {
+                let key: StringDeserializer<Error> = key.clone().into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        self.next_value(seed)
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for RowAccess<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.idx < self.row.columns().len() {
+            self.next_value(seed).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Numbers can also be read from Yes/No columns, as 0 or 1, from Currency
+/// and Decimal columns, and parsed from text.
+///
+/// Whole Currency and Decimal values are integers, and others are parsed
+/// from their exact text, so they are rounded once.
+macro_rules! deserialize_number {
+    ($($method:ident $visit:ident $t:ty)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.value.value()? {
+                    Value::Bool(v) => visitor.visit_u8(v as u8),
+                    Value::Money(v) if v % 10000 == 0 => visitor.visit_i64(v / 10000),
+                    Value::Money(v) => visitor.visit_f64(parse_f64(v as i128, 4)?),
+                    Value::Numeric { value, scale, .. } => match 10i128.checked_pow(scale as u32) {
+                        Some(unit) if value % unit == 0 => visitor.visit_i128(value / unit),
+                        _ => visitor.visit_f64(parse_f64(value, scale)?),
+                    },
+                    Value::Text(v) | Value::Memo(v) => {
+                        visitor.$visit(serde_plain::from_str::<$t>(&v)?)
+                    }
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+/// Nearest `f64` of a fixed-point value.
+fn parse_f64(value: i128, scale: u8) -> Result<f64, Error> {
+    value::decimal_text(value, scale)
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+/// Deserializer of a single cell.
+pub(crate) struct ValueDeserializer<'a> {
+    value: ValueRef<'a>,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    pub(crate) fn new(value: ValueRef<'a>) -> Self {
+        Self { value }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.value()? {
+            Value::Null => visitor.visit_none(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::Byte(v) => visitor.visit_u8(v),
+            Value::Int(v) => visitor.visit_i16(v),
+            Value::LongInt(v) => visitor.visit_i32(v),
+            // Exact text, which decimal types parse without rounding.
+            Value::Money(v) => visitor.visit_string(value::decimal_text(v as i128, 4)),
+            Value::Numeric { value, scale, .. } => {
+                visitor.visit_string(value::decimal_text(value, scale))
+            }
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::Text(v) | Value::Memo(v) => visitor.visit_string(v),
+            Value::Binary(v) | Value::Ole(v) => visitor.visit_byte_buf(v),
+            Value::DateTime(_) | Value::Guid(_) => visitor.visit_str(self.value.as_str()?),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.value()? {
+            Value::Text(v) | Value::Memo(v) => visitor.visit_bool(serde_plain::from_str(&v)?),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.value.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    /// Date/times are ISO 8601 text, and Replication IDs hyphenated text,
+    /// which date, time and UUID types parse.
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.value()? {
+            v @ Value::DateTime(_) => visitor.visit_string(DateTime::try_from(&v)?.iso()),
+            Value::Guid(v) => visitor.visit_string(GuidFormat::Hyphenated.format(&v)),
+            _ => visitor.visit_str(self.value.as_str()?),
+        }
+    }
+
+    /// Strings are the text of the cell, as rendered by libmdb.
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.value.as_str()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.value()? {
+            Value::Binary(v) | Value::Ole(v) => visitor.visit_byte_buf(v),
+            _ => visitor.visit_bytes(self.value.as_str()?.as_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// Sequences are the bytes of BINARY and OLE values, and of the text of
+    /// others. NULL is empty.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let bytes = match self.value.value()? {
+            Value::Binary(v) | Value::Ole(v) => v,
+            Value::Null => vec![],
+            _ => self.value.as_str()?.as_bytes().to_vec(),
+        };
+        visitor.visit_seq(SeqDeserializer::new(bytes.into_iter()))
+    }
+
+    /// Date/times are `(year, ordinal)` as a tuple of 2, and `(year, ordinal,
+    /// hour, minute, second, nanosecond)` as a tuple of 6, like the `time`
+    /// crate expects.
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        match (self.value.value()?, len) {
+            (v @ Value::DateTime(_), 2 | 6) => {
+                let dt = DateTime::try_from(&v)?;
+                let (hour, minute, second, milli) = dt.time();
+                let fields = [
+                    dt.year,
+                    dt.ordinal as i64,
+                    hour as i64,
+                    minute as i64,
+                    second as i64,
+                    milli as i64 * 1_000_000,
+                ];
+                visitor.visit_seq(SeqDeserializer::new(fields.into_iter().take(len)))
+            }
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.value.as_str()?.into_deserializer())
+    }
+
+    deserialize_number! {
+        deserialize_i8 visit_i8 i8 deserialize_i16 visit_i16 i16
+        deserialize_i32 visit_i32 i32 deserialize_i64 visit_i64 i64
+        deserialize_i128 visit_i128 i128 deserialize_u8 visit_u8 u8
+        deserialize_u16 visit_u16 u16 deserialize_u32 visit_u32 u32
+        deserialize_u64 visit_u64 u64 deserialize_u128 visit_u128 u128
+        deserialize_f32 visit_f32 f32 deserialize_f64 visit_f64 f64
+    }
+
+    forward_to_deserialize_any! {
+        char unit unit_struct tuple_struct map struct identifier ignored_any
+    }
+}
@@ -28,7 +28,11 @@ pub enum Error {
     #[error(transparent)]
     Utf8Error(#[from] std::str::Utf8Error),
 
-    /// Poison Error for `MutexGuard<Mdb>`
+    /// Error reading a database into memory.
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    /// Poison Error for the locks of a connection
     #[error("{0}")]
     MutexPoisonError(String),
 
@@ -36,9 +40,65 @@ pub enum Error {
     #[error("invalid index to row results: {0}")]
     InvalidRowIndex(usize),
 
+    /// Error when access to row value with unknown column name
+    #[error("invalid column name: {0}")]
+    InvalidColumnName(String),
+
+    /// Error when raw data of a column does not match its type
+    #[error("invalid data for column: {0}")]
+    InvalidColumnData(String),
+
+    /// Error when a column is not of the type an operation requires
+    #[error("invalid type for column: {0}")]
+    InvalidColumnType(String),
+
     /// Error converting SQL value to `T`
     #[error(transparent)]
     FromSqlError(#[from] serde_plain::Error),
+
+    /// Error converting SQL value to a date or time
+    #[error("invalid date/time: {0}")]
+    InvalidDateTime(String),
+
+    /// Error converting SQL value to a decimal
+    #[error("invalid decimal: {0}")]
+    InvalidDecimal(String),
+
+    /// Error parsing the headers of an OLE object
+    #[error("invalid OLE object: {0}")]
+    InvalidOleObject(String),
+
+    /// Error when tables reference each other in a cycle
+    #[error("cyclic dependency between tables: {0:?}")]
+    DependencyCycle(Vec<String>),
+
+    /// Error when a query parameter has no mdb-sql literal
+    #[error("invalid query parameter: {0}")]
+    InvalidParameter(String),
+
+    /// Error when the number of `?` placeholders does not match the parameters
+    #[error("wrong number of query parameters: {0} placeholders, {1} parameters")]
+    InvalidParameterCount(usize, usize),
+
+    /// Error when a `:name` placeholder has no parameter
+    #[error("invalid parameter name: {0}")]
+    InvalidParameterName(String),
+
+    /// Error parsing or evaluating a query with [`Connection::select`](crate::Connection::select)
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// Error when a query expected to return a row returned none
+    #[error("query returned no rows")]
+    QueryReturnedNoRows,
+
+    /// Error when a query expected to return one row returned more
+    #[error("query returned more than one row")]
+    QueryReturnedMoreThanOneRow,
+
+    /// Error deserializing a row into `T`
+    #[error("{0}")]
+    DeserializeError(String),
 }
 
 impl From<PoisonError<MutexGuard<'_, Mdb>>> for Error {
@@ -47,6 +107,12 @@ impl From<PoisonError<MutexGuard<'_, Mdb>>> for Error {
     }
 }
 
+impl From<PoisonError<MutexGuard<'_, ()>>> for Error {
+    fn from(error: PoisonError<MutexGuard<()>>) -> Self {
+        Self::MutexPoisonError(error.to_string())
+    }
+}
+
 #[cfg(feature = "rusqlite")]
 impl From<Error> for rusqlite::Error {
     fn from(e: Error) -> Self {
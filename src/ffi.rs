@@ -1,28 +1,57 @@
-use glib_sys::{g_free, GPtrArray};
+use glib_sys::{g_free, g_hash_table_foreach, gpointer, GPtrArray};
 use libc::{c_char, c_int, size_t};
+use std::collections::{BTreeMap, HashSet};
 use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{self, Read};
 use std::marker::PhantomData;
+use std::mem;
 use std::os::unix::ffi::OsStrExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::ptr;
+use std::slice;
 use std::string::ToString;
 
 use libmdb_sys::{
-    mdb_bind_column, mdb_fetch_row, mdb_is_system_table, mdb_ole_read_full, mdb_open,
-    mdb_print_col, mdb_print_schema, mdb_read_catalog, mdb_read_columns, mdb_read_table_by_name,
-    mdb_rewind_table, mdb_set_bind_size, mdb_set_default_backend, mdb_sql_exit, mdb_sql_fetch_row,
-    mdb_sql_init, mdb_sql_reset, mdb_sql_run_query, MdbCatalogEntry, MdbColumn,
-    MdbFileFlags_MDB_NOFLAGS, MdbSQL, MdbSQLColumn, MdbTableDef, MDB_OLE, MDB_SHEXP_BULK_INSERT,
-    MDB_SHEXP_INDEXES, MDB_SHEXP_RELATIONS, MDB_TABLE,
+    mdb_bind_column, mdb_col_get_prop, mdb_crack_row, mdb_fetch_row, mdb_find_row,
+    mdb_free_tabledef, mdb_is_system_table, mdb_map_find_next, mdb_ole_read, mdb_ole_read_full,
+    mdb_ole_read_next, mdb_open, mdb_print_col, mdb_print_schema, mdb_read_catalog,
+    mdb_read_columns, mdb_read_indices, mdb_read_table_by_name, mdb_rewind_table,
+    mdb_set_bind_size, mdb_set_default_backend, mdb_sql_exit, mdb_sql_fetch_row, mdb_sql_init,
+    mdb_sql_reset, mdb_sql_run_query, MdbCatalogEntry, MdbColumn, MdbField,
+    MdbFileFlags_MDB_NOFLAGS, MdbFileFlags_MDB_WRITABLE, MdbHandle, MdbIndex, MdbProperties,
+    MdbSQL, MdbSQLColumn, MdbTableDef, MDB_ANY, MDB_ASC, MDB_BIND_SIZE, MDB_BOOL,
+    MDB_IDX_IGNORENULLS, MDB_IDX_REQUIRED, MDB_IDX_UNIQUE, MDB_MAX_COLS, MDB_MAX_IDX_COLS,
+    MDB_MEMO, MDB_MEMO_OVERHEAD, MDB_OLE, MDB_SHEXP_BULK_INSERT, MDB_SHEXP_INDEXES,
+    MDB_SHEXP_RELATIONS, MDB_TABLE,
 };
 
 #[cfg(LIBMDBSQL_GE_VERSION_1)]
-use libmdb_sys::mdb_normalise_and_replace;
+use libmdb_sys::{
+    mdb_normalise_and_replace, mdb_set_boolean_fmt_numbers, mdb_set_boolean_fmt_words,
+    mdb_set_date_fmt, mdb_set_encoding, mdb_set_shortdate_fmt,
+};
 
+use crate::catalog::{
+    CatalogEntry, ColumnInfo, ColumnType, DatabaseStats, IndexColumn, IndexInfo, ObjectType,
+    Properties, SortOrder, TableStats,
+};
 use crate::error::Error;
 
 const EXPORT_BIND_SIZE: usize = 200000;
 
+/// `index_type` of primary keys.
+const INDEX_PRIMARY_KEY: u8 = 1;
+/// `index_type` of the indexes backing relationships.
+const INDEX_FOREIGN_KEY: u8 = 2;
+
+/// Page types, from the first byte of a page.
+const PAGE_DATA: u8 = 0x01;
+const PAGE_TABLE_DEFINITION: u8 = 0x02;
+const PAGE_INDEX_NODE: u8 = 0x03;
+const PAGE_INDEX_LEAF: u8 = 0x04;
+const PAGE_USAGE_MAP: u8 = 0x05;
+
 struct PtrArray<T> {
     arr: *mut GPtrArray,
     _marker: PhantomData<T>,
@@ -37,7 +66,7 @@ impl<T> From<*mut GPtrArray> for PtrArray<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a PtrArray<T> {
+impl<T> IntoIterator for &PtrArray<T> {
     type Item = *const T;
     type IntoIter = PtrArrayIter<T>;
 
@@ -71,25 +100,120 @@ impl<T> Iterator for PtrArrayIter<T> {
     }
 }
 
+/// Value of a cell copied out of the current row.
 #[derive(Debug)]
-pub struct SqlValue(*const c_char);
+pub struct SqlValue {
+    /// Text rendered by libmdb into the bound buffer.
+    text: Vec<u8>,
+    /// Raw field bytes from the data page, `None` for NULL.
+    data: Option<Vec<u8>>,
+    /// Whether the field lies outside of the data page.
+    invalid: bool,
+}
 
 impl SqlValue {
     pub fn get(&self) -> Result<&str, Error> {
-        unsafe { Ok(CStr::from_ptr(self.0).to_str()?) }
+        Ok(std::str::from_utf8(&self.text)?)
+    }
+
+    pub fn data(&self) -> Option<&[u8]> {
+        self.data.as_deref()
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        self.invalid
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text = text.into_bytes();
     }
 }
 
-#[derive(Debug)]
-pub struct SqlColumn(*const MdbSQLColumn);
+/// Position of an OLE object read chunk by chunk.
+pub struct OleChunks {
+    /// Header of the object, as stored in the row.
+    header: Vec<u8>,
+    /// Scratch column keeping the position of the reader in its blob pages.
+    col: Box<MdbColumn>,
+    /// Buffer bound to `col`, holding the current chunk.
+    chunk: Vec<u8>,
+    /// Length of the current chunk.
+    len: usize,
+    state: OleState,
+}
+
+enum OleState {
+    /// Object stored inline in the row, returned as a single chunk.
+    Inline,
+    Start,
+    Next,
+    Done,
+}
+
+impl OleChunks {
+    /// Prepare a chunked read of the OLE object referenced by the raw `field`
+    /// of a row.
+    pub fn new(field: &[u8]) -> Self {
+        let overhead = MDB_MEMO_OVERHEAD as usize;
+        let mut chunks = Self {
+            header: field.get(..overhead).unwrap_or_default().to_vec(),
+            col: Box::new(unsafe { mem::zeroed() }),
+            chunk: vec![0u8; MDB_BIND_SIZE as usize],
+            len: 0,
+            state: OleState::Start,
+        };
+
+        if chunks.header.len() < overhead {
+            chunks.state = OleState::Done;
+        } else if field[3] & 0x80 != 0 {
+            // Inline objects are already part of the field.
+            chunks.chunk = field[overhead..].to_vec();
+            chunks.state = OleState::Inline;
+        }
+        chunks.col.bind_ptr = chunks.chunk.as_mut_ptr() as _;
+        chunks
+    }
+
+    /// Current chunk, empty once the object is read.
+    pub fn chunk(&self) -> &[u8] {
+        &self.chunk[..self.len]
+    }
+}
+
+/// Column of a query, with the definition of the table column it is bound to.
+///
+/// The definition is copied, so it stays valid once the query is reset.
+#[derive(Debug, Clone)]
+pub struct SqlColumn {
+    name: String,
+    bind_type: c_int,
+    /// Position of the table column the query column is bound to.
+    col_num: Option<usize>,
+    col_type: c_int,
+    col_prec: c_int,
+    col_scale: c_int,
+}
 
 impl SqlColumn {
     pub fn name(&self) -> String {
-        unsafe { CStr::from_ptr((*self.0).name).to_str().unwrap().to_string() }
+        self.name.clone()
     }
 
     pub fn bind_type(&self) -> c_int {
-        unsafe { (*self.0).bind_type }
+        self.bind_type
+    }
+
+    /// Access column type, or 0 if the column is not bound to a table column.
+    pub fn col_type(&self) -> c_int {
+        self.col_type
+    }
+
+    pub fn col_prec(&self) -> c_int {
+        self.col_prec
+    }
+
+    pub fn col_scale(&self) -> c_int {
+        self.col_scale
     }
 }
 
@@ -98,14 +222,160 @@ pub struct Mdb(*mut MdbSQL);
 
 unsafe impl Send for Mdb {}
 
+/// File without a name on disk, with a path it can be opened by.
+#[cfg(target_os = "linux")]
+fn anonymous_file() -> Result<(File, PathBuf), Error> {
+    use std::os::unix::io::FromRawFd;
+
+    let fd = unsafe { libc::memfd_create(c"mdbsql".as_ptr() as _, libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    let file = unsafe { File::from_raw_fd(fd) };
+    Ok((file, PathBuf::from(format!("/proc/self/fd/{}", fd))))
+}
+
+/// Temporary file, to be removed once opened.
+#[cfg(not(target_os = "linux"))]
+fn anonymous_file() -> Result<(File, PathBuf), Error> {
+    use std::env;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = env::temp_dir().join(format!(
+        "mdbsql-{}-{}.mdb",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    Ok((file, path))
+}
+
+/// Descriptor of the file opened by libmdb.
+#[cfg(not(LIBMDBSQL_GE_VERSION_1))]
+unsafe fn file_descriptor(mdb: *const MdbHandle) -> c_int {
+    (*(*mdb).f).fd
+}
+
+/// Descriptor of the file opened by libmdb.
+#[cfg(LIBMDBSQL_GE_VERSION_1)]
+unsafe fn file_descriptor(mdb: *const MdbHandle) -> c_int {
+    libc::fileno((*(*mdb).f).stream as *mut libc::FILE)
+}
+
+/// Properties of a property block, by name.
+unsafe fn properties(props: *const MdbProperties) -> Properties {
+    unsafe extern "C" fn insert(key: gpointer, value: gpointer, map: gpointer) {
+        let map = &mut *(map as *mut BTreeMap<String, String>);
+        let key = CStr::from_ptr(key as *const c_char);
+        let value = match value.is_null() {
+            true => String::new(),
+            false => CStr::from_ptr(value as *const c_char)
+                .to_string_lossy()
+                .into_owned(),
+        };
+        map.insert(key.to_string_lossy().into_owned(), value);
+    }
+
+    let mut map = BTreeMap::new();
+    if !props.is_null() && !(*props).hash.is_null() {
+        g_hash_table_foreach(
+            (*props).hash,
+            Some(insert),
+            &mut map as *mut BTreeMap<String, String> as gpointer,
+        );
+    }
+    Properties::new(map)
+}
+
+/// Pages of a file by type.
+#[derive(Debug, Default)]
+struct PageUsage {
+    page_size: u32,
+    pages: u32,
+    by_type: [u32; 6],
+}
+
+/// Offsets of the next page pointer, the entry bitmask and the first entry
+/// of an index page.
+const INDEX_NEXT_PG: usize = 0x0c;
+const JET3_INDEX_BITMASK: usize = 0x16;
+const JET3_INDEX_ENTRIES: usize = 0xf8;
+const JET4_INDEX_BITMASK: usize = 0x1b;
+const JET4_INDEX_ENTRIES: usize = 0x1e0;
+
+/// Child page of the first entry of an index node page.
+///
+/// Set bits of the bitmask mark where entries end, and node entries end with
+/// the big-endian number of their child page.
+fn first_child(page: &[u8]) -> Option<u32> {
+    let (bitmask, entries) = match page.len() {
+        2048 => (JET3_INDEX_BITMASK, JET3_INDEX_ENTRIES),
+        _ => (JET4_INDEX_BITMASK, JET4_INDEX_ENTRIES),
+    };
+    let len = (0..(entries - bitmask) * 8).find(|i| page[bitmask + i / 8] & (1 << (i % 8)) != 0)?;
+    let child = page.get(entries + len.checked_sub(4)?..entries + len)?;
+    Some(u32::from_be_bytes(child.try_into().ok()?)).filter(|&pg| pg != 0)
+}
+
+/// Count the pages of index trees, one level at a time.
+///
+/// Pages of a level are chained by their next page pointer, and node pages
+/// lead to the next level through their first entry. Pages are read with
+/// `pread`, so libmdb's page buffers and file position are left alone.
+fn index_pages(
+    file: &File,
+    page_size: u32,
+    roots: impl IntoIterator<Item = u32>,
+) -> io::Result<u32> {
+    use std::os::unix::fs::FileExt;
+
+    let mut page = vec![0u8; page_size as usize];
+    let mut seen = HashSet::new();
+    let mut count = 0;
+    for root in roots {
+        let mut level = Some(root);
+        while let Some(first) = level.take() {
+            let mut pg = first;
+            while pg != 0 && seen.insert(pg) {
+                file.read_exact_at(&mut page, pg as u64 * page_size as u64)?;
+                match page[0] {
+                    PAGE_INDEX_NODE if level.is_none() => level = first_child(&page),
+                    PAGE_INDEX_NODE | PAGE_INDEX_LEAF => {}
+                    _ => break,
+                }
+                count += 1;
+                pg = u32::from_le_bytes(page[INDEX_NEXT_PG..INDEX_NEXT_PG + 4].try_into().unwrap());
+            }
+        }
+    }
+    Ok(count)
+}
+
 impl Drop for Mdb {
     fn drop(&mut self) {
         unsafe { mdb_sql_exit(self.0) }
     }
 }
 
+/// Options which must be set before a file is opened.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    pub writable: bool,
+    pub jet3_charset: Option<String>,
+}
+
 impl Mdb {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::open_with(path, &OpenOptions::default())
+    }
+
+    pub fn open_with<P: AsRef<Path>>(path: P, options: &OpenOptions) -> Result<Self, Error> {
         let path = path.as_ref();
         if !path.is_file() {
             return Err(Error::InvalidPath(path.to_path_buf()));
@@ -114,47 +384,286 @@ impl Mdb {
         let c_path = CString::new(path.as_os_str().as_bytes())?;
         let c_path = c_path.as_ptr();
 
-        unsafe {
-            let mdb_handle = mdb_open(c_path, MdbFileFlags_MDB_NOFLAGS);
+        // Older versions only read the charset from the environment.
+        #[cfg(not(LIBMDBSQL_GE_VERSION_1))]
+        if options.jet3_charset.is_some() {
+            return Err(Error::MdbSqlError(
+                "Jet3 charset requires libmdbsql 1.0".to_string(),
+            ));
+        }
+
+        let flags = if options.writable {
+            MdbFileFlags_MDB_WRITABLE
+        } else {
+            MdbFileFlags_MDB_NOFLAGS
+        };
+
+        let mdb = unsafe {
+            let mdb_handle = mdb_open(c_path, flags);
             if mdb_handle.is_null() {
-                Err(Error::InvalidMdbFile(path.to_path_buf()))
-            } else {
-                let db_ptr = mdb_sql_init();
-                (*db_ptr).mdb = mdb_handle;
-                Ok(Mdb(db_ptr))
+                return Err(Error::InvalidMdbFile(path.to_path_buf()));
             }
+            let db_ptr = mdb_sql_init();
+            (*db_ptr).mdb = mdb_handle;
+            Mdb(db_ptr)
+        };
+
+        #[cfg(LIBMDBSQL_GE_VERSION_1)]
+        if let Some(charset) = &options.jet3_charset {
+            let charset = CString::new(charset.as_str())?;
+            unsafe { mdb_set_encoding((*mdb.0).mdb, charset.as_ptr()) };
         }
+
+        Ok(mdb)
+    }
+
+    /// Open a database read from `reader`, through an anonymous file.
+    pub fn open_reader<R: Read>(reader: &mut R, options: &OpenOptions) -> Result<Self, Error> {
+        let (mut file, path) = anonymous_file()?;
+        io::copy(reader, &mut file)?;
+        let mdb = Self::open_with(&path, options);
+
+        // libmdb keeps its own descriptor of the file
+        #[cfg(not(target_os = "linux"))]
+        let _ = std::fs::remove_file(&path);
+
+        mdb
     }
 
     pub fn table_names(&self) -> Vec<String> {
+        self.catalog(false)
+            .into_iter()
+            .filter(|e| e.object_type == ObjectType::Table)
+            .map(|e| e.name)
+            .collect()
+    }
+
+    /// Objects of every type in the catalog.
+    pub fn catalog(&self, include_system: bool) -> Vec<CatalogEntry> {
         unsafe {
             let mdb = (*self.0).mdb;
-            mdb_read_catalog(mdb, MDB_TABLE);
+            mdb_read_catalog(mdb, MDB_ANY);
 
             Into::<PtrArray<MdbCatalogEntry>>::into((*mdb).catalog)
                 .into_iter()
-                .filter(|e| mdb_is_system_table(*e as _) == 0)
-                .map(|e| (*e).object_name)
-                .map(|n| CStr::from_ptr(n.as_ptr()).to_str().unwrap().to_string())
+                .map(|e| CatalogEntry {
+                    name: CStr::from_ptr((*e).object_name.as_ptr())
+                        .to_string_lossy()
+                        .into_owned(),
+                    object_type: (*e).object_type.into(),
+                    id: (*e).table_pg as u32,
+                    flags: (*e).flags,
+                    system: mdb_is_system_table(e as _) != 0,
+                    link: None,
+                })
+                .filter(|e| include_system || !e.system)
                 .collect()
         }
     }
 
     /// Columns for current table
     pub fn sql_columns(&self) -> Vec<SqlColumn> {
+        let table_cols: Vec<*const MdbColumn> = unsafe {
+            let table = (*self.0).cur_table;
+            if table.is_null() {
+                vec![]
+            } else {
+                Into::<PtrArray<MdbColumn>>::into((*table).columns)
+                    .into_iter()
+                    .collect()
+            }
+        };
+
         unsafe { Into::<PtrArray<MdbSQLColumn>>::into((*self.0).columns) }
             .into_iter()
-            .map(SqlColumn)
+            .map(|sql_col| unsafe {
+                let name = CStr::from_ptr((*sql_col).name);
+                let col = table_cols.iter().enumerate().find(|(_, c)| {
+                    let col_name = CStr::from_ptr((***c).name.as_ptr());
+                    col_name.to_bytes().eq_ignore_ascii_case(name.to_bytes())
+                });
+                SqlColumn {
+                    name: name.to_str().unwrap().to_string(),
+                    bind_type: (*sql_col).bind_type,
+                    col_num: col.map(|(i, _)| i),
+                    col_type: col.map_or(0, |(_, c)| (**c).col_type),
+                    col_prec: col.map_or(0, |(_, c)| (**c).col_prec),
+                    col_scale: col.map_or(0, |(_, c)| (**c).col_scale),
+                }
+            })
             .collect()
     }
 
-    pub fn sql_bound_values(&self) -> Vec<SqlValue> {
+    /// Values of the current row for `columns`.
+    ///
+    /// Values are copied out of the bound buffers and the page buffer, so
+    /// they stay valid after the next row is fetched.
+    pub fn sql_bound_values(&self, columns: &[SqlColumn]) -> Vec<SqlValue> {
+        let fields = self.crack_current_row();
+        let cracked = fields.is_some();
+        let fields = fields.unwrap_or_default();
+
         unsafe { Into::<PtrArray<c_char>>::into((*self.0).bound_values) }
             .into_iter()
-            .map(SqlValue)
+            .zip(columns)
+            .map(|(text, column)| {
+                let text = unsafe { CStr::from_ptr(text).to_bytes().to_vec() };
+                let field = column.col_num.and_then(|i| fields.get(i));
+                let data = match field {
+                    // Boolean values live in the null bitmap.
+                    Some(f) if column.col_type == MDB_BOOL as c_int => {
+                        Ok(Some(vec![(f.is_null == 0) as u8]))
+                    }
+                    Some(f) if f.is_null != 0 => Ok(None),
+                    Some(f) => {
+                        let pg_buf = unsafe { &(*(*self.0).mdb).pg_buf };
+                        let bytes = usize::try_from(f.start).ok().and_then(|start| {
+                            let end = start.checked_add(usize::try_from(f.siz).ok()?)?;
+                            pg_buf.get(start..end)
+                        });
+                        bytes.map(|b| Some(b.to_vec())).ok_or(())
+                    }
+                    // Table columns of a row which could not be located.
+                    None if column.col_num.is_some() && !cracked => Err(()),
+                    // Computed columns have no null flag, and libmdb renders
+                    // NULL as empty text.
+                    None if text.is_empty() => Ok(None),
+                    None => Ok(Some(text.clone())),
+                };
+                SqlValue {
+                    text,
+                    invalid: data.is_err(),
+                    data: data.unwrap_or_default(),
+                }
+            })
             .collect()
     }
 
+    /// Split the current row of the current table into fields, in the order of
+    /// the table columns.
+    ///
+    /// `mdb_fetch_row` reads the row into the page buffer, from a table scan
+    /// or from an index scan alike, then advances `cur_row` past it. Returns
+    /// `None` if the page buffer does not hold that row of the table.
+    fn crack_current_row(&self) -> Option<Vec<MdbField>> {
+        unsafe {
+            let table = (*self.0).cur_table;
+            if table.is_null() {
+                return Some(vec![]);
+            }
+            let mdb = (*self.0).mdb;
+            let pg_buf = &(*mdb).pg_buf;
+
+            let row = (*table).cur_row.checked_sub(1)?;
+            let owner = u32::from_le_bytes(pg_buf[4..8].try_into().unwrap());
+            let row_count_offset = (*(*mdb).fmt).row_count_offset as usize;
+            let num_rows = u16::from_le_bytes(
+                pg_buf[row_count_offset..row_count_offset + 2]
+                    .try_into()
+                    .unwrap(),
+            );
+            if pg_buf[0] != PAGE_DATA
+                || owner != (*(*table).entry).table_pg as u32
+                || row >= num_rows as u32
+            {
+                return None;
+            }
+
+            let mut row_start: c_int = 0;
+            let mut row_size: size_t = 0;
+            if mdb_find_row(mdb, row as c_int, &mut row_start, &mut row_size) != 0 {
+                return None;
+            }
+
+            let mut fields: Vec<MdbField> = vec![mem::zeroed(); MDB_MAX_COLS as usize];
+            #[cfg(LIBMDBSQL_GE_VERSION_1)]
+            let num_fields = mdb_crack_row(table, row_start, row_size, fields.as_mut_ptr());
+            #[cfg(not(LIBMDBSQL_GE_VERSION_1))]
+            let num_fields = mdb_crack_row(
+                table,
+                row_start,
+                row_start + row_size as c_int - 1,
+                fields.as_mut_ptr(),
+            );
+            fields.truncate(num_fields.max(0) as usize);
+            Some(fields)
+        }
+    }
+
+    /// Read the whole OLE object referenced by the raw `field` of a row.
+    pub fn ole_read(&self, field: &[u8]) -> Vec<u8> {
+        let overhead = MDB_MEMO_OVERHEAD as usize;
+        if field.len() < overhead {
+            return vec![];
+        }
+
+        let ole_len = u32::from_le_bytes([field[0], field[1], field[2], field[3]]);
+
+        // Inline objects are stored right after the header and are already
+        // part of the field.
+        if ole_len & 0x80000000 != 0 {
+            return field[overhead..].to_vec();
+        }
+
+        unsafe {
+            let mdb = (*self.0).mdb;
+
+            // The header is read from the bind buffer of the column, which then
+            // receives each chunk, so a scratch column keeps the query columns
+            // untouched.
+            let mut bind_value = vec![0u8; MDB_BIND_SIZE as usize];
+            bind_value[..overhead].copy_from_slice(&field[..overhead]);
+            let mut col: MdbColumn = mem::zeroed();
+            col.bind_ptr = bind_value.as_mut_ptr() as _;
+
+            let mut len: size_t = 0;
+            let ole_value = mdb_ole_read_full(mdb, &mut col, &mut len);
+            if ole_value.is_null() {
+                return vec![];
+            }
+            let data = slice::from_raw_parts(ole_value as *const u8, len).to_vec();
+            libc::free(ole_value);
+            data
+        }
+    }
+
+    /// Read the next chunk of an OLE object into `chunks`, returning its
+    /// length, or 0 at the end of the object.
+    pub fn ole_read_chunk(&self, chunks: &mut OleChunks) -> usize {
+        let header = chunks.header.as_mut_ptr() as _;
+        chunks.len = match chunks.state {
+            OleState::Inline => chunks.chunk.len(),
+            OleState::Start => unsafe {
+                mdb_ole_read((*self.0).mdb, &mut *chunks.col, header, chunks.chunk.len())
+            },
+            OleState::Next => unsafe { mdb_ole_read_next((*self.0).mdb, &mut *chunks.col, header) },
+            OleState::Done => 0,
+        };
+        chunks.state = match chunks.state {
+            _ if chunks.len == 0 => OleState::Done,
+            OleState::Start | OleState::Next => OleState::Next,
+            _ => OleState::Done,
+        };
+        chunks.len
+    }
+
+    /// Stop rendering the Memo columns of the current query, so their values
+    /// are not read and stay empty.
+    pub fn unbind_memo_columns(&self) {
+        unsafe {
+            let table = (*self.0).cur_table;
+            if table.is_null() {
+                return;
+            }
+            for col in &Into::<PtrArray<MdbColumn>>::into((*table).columns) {
+                let col = col as *mut MdbColumn;
+                if (*col).col_type == MDB_MEMO as c_int {
+                    (*col).bind_ptr = ptr::null_mut();
+                }
+            }
+        }
+    }
+
     pub fn sql_run_query(&self, query: *const c_char) {
         unsafe {
             mdb_sql_run_query(self.0, query);
@@ -200,6 +709,59 @@ impl Mdb {
         }
     }
 
+    /// Set the strftime format of date/time values.
+    #[cfg(LIBMDBSQL_GE_VERSION_1)]
+    pub fn set_date_format(&self, format: &str) -> Result<(), Error> {
+        let format = CString::new(format)?;
+        unsafe { mdb_set_date_fmt((*self.0).mdb, format.as_ptr()) };
+        Ok(())
+    }
+
+    // the format is global before 1.0
+    #[cfg(not(LIBMDBSQL_GE_VERSION_1))]
+    pub fn set_date_format(&self, _format: &str) -> Result<(), Error> {
+        Err(Error::MdbSqlError(
+            "Date format requires libmdbsql 1.0".to_string(),
+        ))
+    }
+
+    /// Set the strftime format of date values without a time.
+    #[cfg(LIBMDBSQL_GE_VERSION_1)]
+    pub fn set_short_date_format(&self, format: &str) -> Result<(), Error> {
+        let format = CString::new(format)?;
+        unsafe { mdb_set_shortdate_fmt((*self.0).mdb, format.as_ptr()) };
+        Ok(())
+    }
+
+    #[cfg(not(LIBMDBSQL_GE_VERSION_1))]
+    pub fn set_short_date_format(&self, _format: &str) -> Result<(), Error> {
+        Err(Error::MdbSqlError(
+            "Short date format requires libmdbsql 1.0".to_string(),
+        ))
+    }
+
+    /// Render Yes/No values as `TRUE`/`FALSE` instead of `1`/`0`.
+    #[cfg(LIBMDBSQL_GE_VERSION_1)]
+    pub fn set_boolean_words(&self, words: bool) -> Result<(), Error> {
+        unsafe {
+            match words {
+                true => mdb_set_boolean_fmt_words((*self.0).mdb),
+                false => mdb_set_boolean_fmt_numbers((*self.0).mdb),
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(LIBMDBSQL_GE_VERSION_1))]
+    pub fn set_boolean_words(&self, words: bool) -> Result<(), Error> {
+        match words {
+            true => Err(Error::MdbSqlError(
+                "Boolean words require libmdbsql 1.0".to_string(),
+            )),
+            false => Ok(()),
+        }
+    }
+
     pub fn schema(&self, table_name: &str) -> Result<String, Error> {
         unsafe {
             let mdb = (*self.0).mdb;
@@ -223,8 +785,10 @@ impl Mdb {
     }
 
     pub fn read_table(&self, table_name: &str) -> Result<*mut MdbTableDef, Error> {
+        let c_table_name = CString::new(table_name)?;
         unsafe {
-            let table = mdb_read_table_by_name((*self.0).mdb, table_name.as_ptr() as _, MDB_TABLE);
+            let table =
+                mdb_read_table_by_name((*self.0).mdb, c_table_name.as_ptr() as _, MDB_TABLE);
             if table.is_null() {
                 Err(Error::MdbSqlError(format!(
                     "Table {} does not exist in this database.",
@@ -238,6 +802,225 @@ impl Mdb {
         }
     }
 
+    /// Definitions of the columns of a table.
+    pub fn table_info(&self, table_name: &str) -> Result<Vec<ColumnInfo>, Error> {
+        let table = self.read_table(table_name)?;
+
+        unsafe {
+            let columns = Into::<PtrArray<MdbColumn>>::into((*table).columns)
+                .into_iter()
+                .map(|c| {
+                    let column_type = ColumnType::from((*c).col_type);
+                    let required = mdb_col_get_prop(c, c"Required".as_ptr() as _);
+                    // Yes/No values are never NULL
+                    let required = column_type == ColumnType::Bool
+                        || (!required.is_null() && *required == b'y' as c_char);
+                    ColumnInfo {
+                        name: CStr::from_ptr((*c).name.as_ptr())
+                            .to_string_lossy()
+                            .into_owned(),
+                        column_type,
+                        size: (*c).col_size,
+                        precision: (*c).col_prec as u8,
+                        scale: (*c).col_scale as u8,
+                        fixed: (*c).is_fixed != 0,
+                        nullable: !required,
+                        auto_number: (*c).is_long_auto != 0,
+                        auto_guid: (*c).is_uuid_auto != 0,
+                    }
+                })
+                .collect();
+            mdb_free_tabledef(table);
+            Ok(columns)
+        }
+    }
+
+    /// Definitions of the indexes of a table.
+    pub fn indexes(&self, table_name: &str) -> Result<Vec<IndexInfo>, Error> {
+        let table = self.read_table(table_name)?;
+
+        unsafe {
+            mdb_read_indices(table);
+
+            let columns: Vec<*const MdbColumn> =
+                Into::<PtrArray<MdbColumn>>::into((*table).columns)
+                    .into_iter()
+                    .collect();
+
+            let indexes = Into::<PtrArray<MdbIndex>>::into((*table).indices)
+                .into_iter()
+                .map(|idx| {
+                    let num_keys = ((*idx).num_keys as usize).min(MDB_MAX_IDX_COLS as usize);
+                    let index_columns = (0..num_keys)
+                        .filter_map(|i| {
+                            // key columns are numbered from 1
+                            let col =
+                                columns.get(((*idx).key_col_num[i] as usize).checked_sub(1)?)?;
+                            Some(IndexColumn {
+                                name: CStr::from_ptr((**col).name.as_ptr())
+                                    .to_string_lossy()
+                                    .into_owned(),
+                                order: match (*idx).key_col_order[i] as u32 {
+                                    MDB_ASC => SortOrder::Ascending,
+                                    _ => SortOrder::Descending,
+                                },
+                            })
+                        })
+                        .collect();
+                    let flags = (*idx).flags as u32;
+
+                    IndexInfo {
+                        name: CStr::from_ptr((*idx).name.as_ptr())
+                            .to_string_lossy()
+                            .into_owned(),
+                        columns: index_columns,
+                        primary_key: (*idx).index_type == INDEX_PRIMARY_KEY,
+                        foreign_key: (*idx).index_type == INDEX_FOREIGN_KEY,
+                        unique: flags & MDB_IDX_UNIQUE != 0,
+                        ignore_nulls: flags & MDB_IDX_IGNORENULLS != 0,
+                        required: flags & MDB_IDX_REQUIRED != 0,
+                    }
+                })
+                .collect();
+            mdb_free_tabledef(table);
+            Ok(indexes)
+        }
+    }
+
+    /// Properties of a table.
+    pub fn table_properties(&self, table_name: &str) -> Result<Properties, Error> {
+        let table = self.read_table(table_name)?;
+
+        unsafe {
+            let props = properties((*table).props);
+            mdb_free_tabledef(table);
+            Ok(props)
+        }
+    }
+
+    /// Properties of the columns of a table, by column name.
+    pub fn column_properties(&self, table_name: &str) -> Result<Vec<(String, Properties)>, Error> {
+        let table = self.read_table(table_name)?;
+
+        unsafe {
+            let props = Into::<PtrArray<MdbColumn>>::into((*table).columns)
+                .into_iter()
+                .map(|c| {
+                    let name = CStr::from_ptr((*c).name.as_ptr())
+                        .to_string_lossy()
+                        .into_owned();
+                    (name, properties((*c).props))
+                })
+                .collect();
+            mdb_free_tabledef(table);
+            Ok(props)
+        }
+    }
+
+    /// Row count and storage of a table.
+    pub fn table_stats(&self, table_name: &str) -> Result<TableStats, Error> {
+        let table = self.read_table(table_name)?;
+        let stats = unsafe { self.storage(table) };
+        unsafe { mdb_free_tabledef(table) };
+        stats
+    }
+
+    /// Storage summary of the database, with statistics of user tables.
+    pub fn database_stats(&self) -> Result<DatabaseStats, Error> {
+        let usage = self.page_usage()?;
+
+        let tables = self
+            .catalog(false)
+            .into_iter()
+            .filter(|e| e.object_type == ObjectType::Table)
+            .map(|e| self.table_stats(&e.name))
+            .collect::<Result<_, Error>>()?;
+
+        Ok(DatabaseStats {
+            page_size: usage.page_size,
+            pages: usage.pages,
+            data_pages: usage.by_type[PAGE_DATA as usize],
+            definition_pages: usage.by_type[PAGE_TABLE_DEFINITION as usize],
+            index_pages: usage.by_type[PAGE_INDEX_NODE as usize]
+                + usage.by_type[PAGE_INDEX_LEAF as usize],
+            usage_map_pages: usage.by_type[PAGE_USAGE_MAP as usize],
+            size: usage.pages as u64 * usage.page_size as u64,
+            tables,
+        })
+    }
+
+    /// Row count and storage of a table definition.
+    ///
+    /// Data pages are those of the usage map of the table, and index pages
+    /// are found by walking the trees of its real indexes.
+    unsafe fn storage(&self, table: *mut MdbTableDef) -> Result<TableStats, Error> {
+        use std::mem::ManuallyDrop;
+        use std::os::unix::io::FromRawFd;
+
+        let mdb = (*self.0).mdb;
+        let page_size = (*(*mdb).fmt).pg_size as u32;
+
+        let mut data_pages = 0;
+        let mut pg = 0;
+        loop {
+            match mdb_map_find_next(mdb, (*table).usage_map, (*table).map_sz as _, pg) {
+                next if next > 0 => pg = next as u32,
+                _ => break,
+            }
+            data_pages += 1;
+        }
+
+        mdb_read_indices(table);
+        let roots: Vec<u32> = Into::<PtrArray<MdbIndex>>::into((*table).indices)
+            .into_iter()
+            .map(|idx| (*idx).first_pg)
+            .collect();
+        // the descriptor is owned by libmdb
+        let file = ManuallyDrop::new(File::from_raw_fd(file_descriptor(mdb)));
+        let index_pages = index_pages(&file, page_size, roots)?;
+
+        Ok(TableStats {
+            name: CStr::from_ptr((*table).name.as_ptr())
+                .to_string_lossy()
+                .into_owned(),
+            num_rows: (*table).num_rows,
+            data_pages,
+            index_pages,
+            size: (data_pages + index_pages) as u64 * page_size as u64,
+        })
+    }
+
+    /// Count pages by type, from the header of each page of the file.
+    ///
+    /// Pages are read with `pread`, so libmdb's page buffers and file
+    /// position are left alone.
+    fn page_usage(&self) -> Result<PageUsage, Error> {
+        use std::mem::ManuallyDrop;
+        use std::os::unix::fs::FileExt;
+        use std::os::unix::io::FromRawFd;
+
+        let (fd, page_size) = unsafe {
+            let mdb = (*self.0).mdb;
+            (file_descriptor(mdb), (*(*mdb).fmt).pg_size as u32)
+        };
+        // the descriptor is owned by libmdb
+        let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+
+        let mut usage = PageUsage {
+            page_size,
+            pages: (file.metadata()?.len() / page_size as u64) as u32,
+            ..Default::default()
+        };
+        let mut page_type = [0u8; 1];
+        for pg in 0..usage.pages {
+            file.read_exact_at(&mut page_type, pg as u64 * page_size as u64)?;
+            if let Some(count) = usage.by_type.get_mut(page_type[0] as usize) {
+                *count += 1;
+            }
+        }
+        Ok(usage)
+    }
+
     pub fn export(&self, table_name: &str) -> Result<String, Error> {
         let quote_text = 1;
         let export_flags = 0;
@@ -249,20 +1032,22 @@ impl Mdb {
         unsafe {
             let mdb = (*self.0).mdb;
 
-            let mut bound_values = vec![];
-
-            for i in 1..=(*table).num_cols {
-                let mut bind_value = vec![0u8; EXPORT_BIND_SIZE];
-                let mut bind_len = 0;
+            let num_cols = (*table).num_cols as usize;
+            let mut bound_values = vec![vec![0u8; EXPORT_BIND_SIZE]; num_cols];
+            // lengths are written by libmdb, so they must not move after binding
+            let mut bound_lens: Vec<c_int> = vec![0; num_cols];
 
+            for (i, (bind_value, bind_len)) in bound_values
+                .iter_mut()
+                .zip(bound_lens.iter_mut())
+                .enumerate()
+            {
                 mdb_bind_column(
                     table,
-                    i as c_int,
+                    i as c_int + 1,
                     bind_value.as_mut_ptr() as _,
-                    &mut bind_len,
+                    bind_len,
                 );
-
-                bound_values.push((bind_value, bind_len));
             }
 
             let mut buf: *mut c_char = ptr::null_mut();
@@ -282,39 +1067,38 @@ impl Mdb {
 
             while mdb_fetch_row(table) == 1 {
                 let quoted_name = quote((*table).name.as_ptr());
-                libc::fputs("INSERT INTO \0".as_ptr() as _, mem_fd);
+                libc::fputs(c"INSERT INTO ".as_ptr(), mem_fd);
                 libc::fputs(quoted_name, mem_fd);
-                libc::fputs(" (\0".as_ptr() as _, mem_fd);
+                libc::fputs(c" (".as_ptr(), mem_fd);
                 g_free(quoted_name as _);
 
                 let cols: PtrArray<MdbColumn> = (*table).columns.into();
 
                 for (i, c) in cols.into_iter().enumerate() {
                     if i > 0 {
-                        libc::fputs(", \0".as_ptr() as _, mem_fd);
+                        libc::fputs(c", ".as_ptr(), mem_fd);
                     }
                     let quoted_name = (*c).name.as_ptr() as _;
                     libc::fputs(normalize(quoted_name), mem_fd);
                 }
 
-                libc::fputs(") VALUES (\0".as_ptr() as _, mem_fd);
+                libc::fputs(c") VALUES (".as_ptr(), mem_fd);
 
                 for (i, c) in cols.into_iter().enumerate() {
                     let col_type = (*c).col_type;
 
-                    let (value, length) = if col_type == MDB_OLE as i32 {
-                        let len_ptr = ptr::null_mut();
-                        let bind_ptr = mdb_ole_read_full(mdb, c as _, len_ptr);
-                        (bind_ptr, len_ptr as *mut i32)
+                    let is_ole = col_type == MDB_OLE as i32;
+                    let (value, length) = if is_ole {
+                        // the bound value holds the header of the object
+                        let mut ole_len: size_t = 0;
+                        let ole_value = mdb_ole_read_full(mdb, c as _, &mut ole_len);
+                        (ole_value as *mut c_char, ole_len as c_int)
                     } else {
-                        (
-                            bound_values[i].0.as_mut_ptr() as _,
-                            &mut bound_values[i].1 as _,
-                        )
+                        (bound_values[i].as_mut_ptr() as _, bound_lens[i])
                     };
 
                     if i > 0 {
-                        libc::fputs(",\0".as_ptr() as _, mem_fd);
+                        libc::fputs(c",".as_ptr(), mem_fd);
                     }
 
                     mdb_print_col(
@@ -322,13 +1106,17 @@ impl Mdb {
                         value as _,
                         quote_text,
                         col_type,
-                        *length,
-                        "\"\0".as_ptr() as _,
+                        length,
+                        c"\"".as_ptr() as _,
                         ptr::null_mut(),
                         export_flags,
                     );
+
+                    if is_ole {
+                        libc::free(value as _);
+                    }
                 }
-                libc::fputs(");\0".as_ptr() as _, mem_fd);
+                libc::fputs(c");".as_ptr(), mem_fd);
             }
 
             libc::fclose(mem_fd);
@@ -3,6 +3,8 @@
 //! This is a simple wrapper for [libmdbsql](https://github.com/mdbtools/mdbtools) in Rust.
 //!
 //! The implemented SQL subset is limited, please refer to [mdb-sql](https://man.cx/mdb-sql(1)).
+//! [`Connection::select`] adds ORDER BY, GROUP BY, HAVING, aggregates,
+//! DISTINCT, and INNER and LEFT JOIN, evaluated in memory.
 
 //!
 //! # Example
@@ -31,12 +33,36 @@
 //! # Ok(())
 //! # }
 //! ````
+mod catalog;
+#[cfg(feature = "chrono")]
+mod chrono;
+mod de;
 #[deny(missing_docs)]
 mod error;
 mod ffi;
 pub mod mdbsql;
+pub mod ole;
+mod params;
 #[cfg(feature = "rusqlite")]
 mod rusqlite;
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+mod saved_query;
+mod select;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(feature = "uuid")]
+mod uuid;
+mod value;
 
+pub use crate::catalog::{
+    dependency_order, CatalogEntry, ColumnInfo, ColumnType, DatabaseStats, IndexColumn, IndexInfo,
+    Link, ObjectType, Properties, Relationship, SortOrder, TableStats,
+};
 pub use crate::error::Error;
-pub use crate::mdbsql::Connection;
+pub use crate::mdbsql::{BooleanFormat, Connection, ConnectionBuilder, ResultSet, Statement};
+pub use crate::params::{Params, ToSql};
+#[cfg(feature = "rusqlite")]
+pub use crate::rusqlite::OpenMdb;
+pub use crate::saved_query::{QueryParameter, QueryType, SavedQuery};
+pub use crate::value::{GuidFormat, Value, ValueRef};
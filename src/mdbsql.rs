@@ -1,114 +1,1075 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::ffi::CString;
-use std::os::raw::c_char;
-use std::path::Path;
-use std::sync::{Mutex, MutexGuard};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ops::Range;
+use std::os::raw::{c_char, c_int};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
+use libmdb_sys::MDB_OLE;
+
+use crate::catalog::{
+    self, CatalogEntry, ColumnInfo, DatabaseStats, IndexInfo, ObjectType, Properties, Relationship,
+    TableStats,
+};
+use crate::de::{RowDeserializer, ValueDeserializer};
 use crate::error::Error;
-use crate::ffi::{Mdb, SqlColumn, SqlValue};
+use crate::ffi::{Mdb, OleChunks, OpenOptions, SqlColumn, SqlValue};
+use crate::params::{self, Params, Template};
+use crate::saved_query::{self, SavedQuery};
+pub use crate::select::ResultSet;
+use crate::select::{bracket, Select};
+use crate::value::{GuidFormat, Value, ValueRef};
 
 /// A connection to a mdb database.
 #[derive(Debug)]
 pub struct Connection {
-    db: Mutex<Mdb>,
+    db: Arc<Mutex<Mdb>>,
+    /// Held by [`Rows`] while a query is fetched, and by anything else but
+    /// OLE reads, which use the handle between two fetches.
+    query: Mutex<()>,
+    guid_format: GuidFormat,
+    no_memo: bool,
+    links: Links,
+}
+
+/// Databases of linked tables, opened with [`ConnectionBuilder::resolve_links`].
+#[derive(Debug, Default)]
+struct Links {
+    databases: Vec<Connection>,
+    /// Lowercase names of linked tables, to their database and foreign name.
+    tables: HashMap<String, (usize, String)>,
 }
 
 impl Connection {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        Ok(Self {
-            db: Mutex::new(Mdb::open(path)?),
+        Self::builder(path).open()
+    }
+
+    /// Open a database from its bytes, e.g. an uploaded file.
+    pub fn open_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        Self::open_reader(io::Cursor::new(bytes))
+    }
+
+    /// Open a database read from the start of `reader`.
+    ///
+    /// The content is copied into an anonymous file, in memory on Linux.
+    pub fn open_reader<R: Read + Seek>(mut reader: R) -> Result<Self, Error> {
+        reader.seek(SeekFrom::Start(0))?;
+        let mdb = Mdb::open_reader(&mut reader, &OpenOptions::default())?;
+        Ok(Self::new(mdb, GuidFormat::default()))
+    }
+
+    fn new(mdb: Mdb, guid_format: GuidFormat) -> Self {
+        Self {
+            db: Arc::new(Mutex::new(mdb)),
+            query: Mutex::new(()),
+            guid_format,
+            no_memo: false,
+            links: Links::default(),
+        }
+    }
+
+    /// Lock the database, waiting for the rows of a running query to be
+    /// dropped.
+    fn lock(&self) -> Result<DbGuard<'_>, Error> {
+        let query = self.query.lock()?;
+        Ok(DbGuard {
+            mdb: self.db.lock()?,
+            _query: query,
         })
     }
 
-    pub fn prepare(&self, query: &str) -> Result<Rows, Error> {
-        let guard = self.db.lock()?;
+    /// Options to open the database at `path` with.
+    pub fn builder<P: AsRef<Path>>(path: P) -> ConnectionBuilder {
+        ConnectionBuilder::new(path)
+    }
+
+    /// Set the text format of Replication ID values read as strings.
+    pub fn set_guid_format(&mut self, format: GuidFormat) {
+        self.guid_format = format;
+    }
+
+    /// Objects of every type in the catalog, including system tables if
+    /// `include_system` is set.
+    pub fn catalog(&self, include_system: bool) -> Result<Vec<CatalogEntry>, Error> {
+        let mut catalog = self.lock()?.catalog(include_system);
+
+        if catalog
+            .iter()
+            .any(|e| e.object_type == ObjectType::LinkedTable)
+        {
+            let mut links = catalog::links(
+                self.prepare("SELECT Name, Database, ForeignName FROM MSysObjects WHERE Type = 6")?,
+            )?;
+            for entry in &mut catalog {
+                if entry.object_type == ObjectType::LinkedTable {
+                    entry.link = links.remove(&entry.name);
+                }
+            }
+        }
+
+        Ok(catalog)
+    }
+
+    /// Names of user tables.
+    pub fn tables(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .catalog(false)?
+            .into_iter()
+            .filter(|e| e.object_type == ObjectType::Table)
+            .map(|e| e.name)
+            .collect())
+    }
+
+    /// Definitions of the columns of a table.
+    pub fn table_info(&self, table_name: &str) -> Result<Vec<ColumnInfo>, Error> {
+        self.lock()?.table_info(table_name)
+    }
+
+    /// Definitions of the indexes of a table, including its primary key.
+    pub fn indexes(&self, table_name: &str) -> Result<Vec<IndexInfo>, Error> {
+        self.lock()?.indexes(table_name)
+    }
+
+    /// Properties of a table, e.g. its description.
+    pub fn table_properties(&self, table_name: &str) -> Result<Properties, Error> {
+        self.lock()?.table_properties(table_name)
+    }
+
+    /// Properties of the columns of a table, by column name.
+    pub fn column_properties(&self, table_name: &str) -> Result<Vec<(String, Properties)>, Error> {
+        self.lock()?.column_properties(table_name)
+    }
+
+    /// Row count and page usage of a table.
+    ///
+    /// The row count is read from the table definition, without scanning
+    /// the table.
+    pub fn table_stats(&self, table_name: &str) -> Result<TableStats, Error> {
+        self.lock()?.table_stats(table_name)
+    }
+
+    /// Page usage of the database, with statistics of each user table.
+    pub fn database_stats(&self) -> Result<DatabaseStats, Error> {
+        self.lock()?.database_stats()
+    }
+
+    /// Relationships between tables, from `MSysRelationships`.
+    pub fn relationships(&self) -> Result<Vec<Relationship>, Error> {
+        catalog::relationships(self.prepare("SELECT * FROM MSysRelationships")?)
+    }
+
+    /// Queries saved in the database, rebuilt as SQL from `MSysQueries`.
+    pub fn saved_queries(&self) -> Result<Vec<SavedQuery>, Error> {
+        let catalog = self.catalog(true)?;
+        saved_query::saved_queries(self.prepare("SELECT * FROM MSysQueries")?, &catalog)
+    }
+
+    /// Run a query with parameters substituted for its `?` or `:name`
+    /// placeholders.
+    ///
+    /// ```rust
+    /// use mdbsql::{params, Connection, Error};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let conn = Connection::open("./resource/test.mdb")?;
+    /// let mut rows = conn.query("SELECT A FROM Table1 WHERE ID = ?", params![1])?;
+    /// let a: String = rows.next().unwrap().get(0)?;
+    /// assert_eq!(a, "Foo");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query<P: Params>(&self, query: &str, params: P) -> Result<Rows<'_>, Error> {
+        self.prepare(&params::bind(query, &params)?)
+    }
+
+    /// Parse a query once, to run it with different parameters.
+    ///
+    /// The query is not run: its tables and columns are checked against the
+    /// table definitions, which give the names of its result columns.
+    ///
+    /// ```rust
+    /// use mdbsql::{params, Connection, Error};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let conn = Connection::open("./resource/test.mdb")?;
+    /// let stmt = conn.statement("SELECT A FROM Table1 WHERE ID = ?")?;
+    /// for id in [1, 2] {
+    ///     let a: String = stmt.query_row(params![id], |row| row.get(0))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn statement(&self, query: &str) -> Result<Statement<'_>, Error> {
+        let mut stmt = Statement::new(self, query);
+        let select = Select::parse(&stmt.template.with_nulls())?;
+        let columns = select
+            .tables()
+            .iter()
+            .map(|table| self.column_names(table))
+            .collect::<Result<Vec<_>, Error>>()?;
+        stmt.column_names = select.column_names(&columns)?;
+        Ok(stmt)
+    }
+
+    /// Names of the columns of a table, in the database holding it.
+    fn column_names(&self, table: &str) -> Result<Vec<String>, Error> {
+        if let Some((i, foreign_name)) = self.links.tables.get(&table.to_lowercase()) {
+            return self.links.databases[*i].column_names(foreign_name);
+        }
+        Ok(self
+            .table_info(table)?
+            .into_iter()
+            .map(|c| c.name)
+            .collect())
+    }
+
+    /// Run a query with parameters, mapping its first row with `f`.
+    ///
+    /// Returns [`Error::QueryReturnedNoRows`] if the result is empty.
+    ///
+    /// ```rust
+    /// use mdbsql::{params, Connection, Error};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let conn = Connection::open("./resource/test.mdb")?;
+    /// let a: String = conn.query_row("SELECT A FROM Table1 WHERE ID = ?", params![1], |row| {
+    ///     row.get(0)
+    /// })?;
+    /// assert_eq!(a, "Foo");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_row<P, T, F>(&self, query: &str, params: P, f: F) -> Result<T, Error>
+    where
+        P: Params,
+        F: FnOnce(&Row) -> Result<T, Error>,
+    {
+        Statement::new(self, query).query_row(params, f)
+    }
+
+    /// Run a query with parameters, mapping its only row with `f`.
+    ///
+    /// Like [`Connection::query_row`], but returns
+    /// [`Error::QueryReturnedMoreThanOneRow`] if more rows follow.
+    pub fn query_one<P, T, F>(&self, query: &str, params: P, f: F) -> Result<T, Error>
+    where
+        P: Params,
+        F: FnOnce(&Row) -> Result<T, Error>,
+    {
+        Statement::new(self, query).query_one(params, f)
+    }
+
+    /// Run a query with ORDER BY, GROUP BY, HAVING, aggregates, DISTINCT or
+    /// joins, which libmdbsql does not support.
+    ///
+    /// The selected columns, the WHERE clause and LIMIT are run by libmdbsql,
+    /// and the rest of the query is evaluated over the typed values of all
+    /// rows in memory. Aggregates are `COUNT`, `SUM`, `AVG`, `MIN` and `MAX`.
+    ///
+    /// `INNER JOIN` and `LEFT JOIN` read each table with libmdbsql and join
+    /// the rows in memory, hashing them on the equalities of the ON clause.
+    /// The WHERE clause of a join is evaluated in memory, and
+    /// supports comparisons, `IS NULL`, `AND`, `OR` and `NOT`.
+    ///
+    /// ```rust
+    /// use mdbsql::{Connection, Error, Value};
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let conn = Connection::open("./resource/test.mdb")?;
+    /// let result = conn.select("SELECT A FROM Table1 ORDER BY ID DESC", ())?;
+    /// assert_eq!(result.rows[0], vec![Value::Text("fOO".to_string())]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn select<P: Params>(&self, query: &str, params: P) -> Result<ResultSet, Error> {
+        let select = Select::parse(&params::bind(query, &params)?)?;
+        if select.is_join() {
+            return self.select_join(&select);
+        }
+
+        let rows = self.prepare(&select.pushdown())?;
+        let columns: Vec<String> = rows.columns().iter().map(|c| c.name()).collect();
+        let used: Vec<bool> = columns.iter().map(|c| select.uses(c)).collect();
+        let values = rows
+            .map(|row| {
+                (0..used.len())
+                    .map(|i| match used[i] {
+                        true => row.get_value(i),
+                        false => Ok(Value::Null),
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, Error>>()?;
+
+        select.evaluate(&[columns], values)
+    }
+
+    /// Run a query joining tables, one table after the other.
+    ///
+    /// Each table is read once, and its rows are hash-joined to the rows of
+    /// the tables before it. Rows are not looked up by key on an index of the
+    /// joined table: libmdbsql only uses indexes when `MDBOPTS=use_index` is
+    /// set before it is first loaded, and otherwise scans the whole table for
+    /// each key.
+    fn select_join(&self, select: &Select) -> Result<ResultSet, Error> {
+        let tables = select.tables();
+        let columns = tables
+            .iter()
+            .map(|table| self.column_names(table))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let used = select.used_columns(&columns)?;
+
+        let mut rows = self.values(&format!("SELECT * FROM {}", bracket(tables[0])), &used[0])?;
+        for (i, table) in tables.iter().enumerate().skip(1) {
+            let joined = self.values(&format!("SELECT * FROM {}", bracket(table)), &used[i])?;
+            rows = select.join(i, &columns, rows, joined)?;
+        }
+
+        select.evaluate(&columns, rows)
+    }
+
+    /// Typed values of the rows of a query, with unused columns left NULL.
+    fn values(&self, query: &str, used: &[bool]) -> Result<Vec<Vec<Value>>, Error> {
+        self.prepare(query)?
+            .map(|row| {
+                (0..used.len())
+                    .map(|i| match used[i] {
+                        true => row.get_value(i),
+                        false => Ok(Value::Null),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Run a query with libmdbsql.
+    ///
+    /// Joins are not supported by libmdbsql, and are run with
+    /// [`Connection::select`] instead.
+    pub fn prepare(&self, query: &str) -> Result<Rows<'_>, Error> {
+        if Select::parse(query).is_ok_and(|select| select.is_join()) {
+            return Err(Error::InvalidQuery(
+                "joins are only run by Connection::select".to_string(),
+            ));
+        }
+
+        // Queries on linked tables run in the database holding them.
+        if let Some((range, table)) = from_table(query) {
+            if let Some((i, foreign_name)) = self.links.tables.get(&table.to_lowercase()) {
+                let query = format!(
+                    "{}{}{}",
+                    &query[..range.start],
+                    bracket(foreign_name),
+                    &query[range.end..]
+                );
+                return self.links.databases[*i].prepare(&query);
+            }
+        }
 
         let query = CString::new(query)?;
-        let query = query.as_ptr() as *const c_char;
-        guard.sql_run_query(query);
+        let guard = self.lock()?;
+        guard.sql_run_query(query.as_ptr() as *const c_char);
 
         match guard.error_msg() {
-            None => Ok(guard.into()),
+            None => {
+                if self.no_memo {
+                    guard.unbind_memo_columns();
+                }
+                Ok(Rows::new(guard, self.db.clone(), self.guid_format))
+            }
             Some(msg) => Err(Error::MdbSqlError(msg)),
         }
     }
 }
 
+/// Query parsed by [`Connection::statement`], to be run with parameters.
+#[derive(Debug)]
+pub struct Statement<'conn> {
+    conn: &'conn Connection,
+    sql: String,
+    template: Template,
+    /// Names of the result columns.
+    column_names: Vec<String>,
+}
+
+impl<'conn> Statement<'conn> {
+    fn new(conn: &'conn Connection, query: &str) -> Self {
+        Self {
+            conn,
+            sql: query.to_string(),
+            template: Template::parse(query),
+            column_names: vec![],
+        }
+    }
+
+    /// Text of the query, with its placeholders.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Names of the result columns.
+    pub fn column_names(&self) -> Vec<String> {
+        self.column_names.clone()
+    }
+
+    /// Number of result columns.
+    pub fn column_count(&self) -> usize {
+        self.column_names.len()
+    }
+
+    /// Run the query with `params`.
+    pub fn query<P: Params>(&self, params: P) -> Result<Rows<'conn>, Error> {
+        self.conn.prepare(&self.template.render(&params)?)
+    }
+
+    /// Run the query with `params`, mapping each row with `f`.
+    pub fn query_map<P, T, F>(&self, params: P, f: F) -> Result<MappedRows<'conn, F>, Error>
+    where
+        P: Params,
+        F: FnMut(&Row) -> Result<T, Error>,
+    {
+        Ok(MappedRows {
+            rows: self.query(params)?,
+            map: f,
+        })
+    }
+
+    /// Run the query with `params`, mapping its first row with `f`.
+    ///
+    /// Returns [`Error::QueryReturnedNoRows`] if the result is empty.
+    pub fn query_row<P, T, F>(&self, params: P, f: F) -> Result<T, Error>
+    where
+        P: Params,
+        F: FnOnce(&Row) -> Result<T, Error>,
+    {
+        let row = self
+            .query(params)?
+            .next()
+            .ok_or(Error::QueryReturnedNoRows)?;
+        f(&row)
+    }
+
+    /// Run the query with `params`, mapping its only row with `f`.
+    ///
+    /// Returns [`Error::QueryReturnedNoRows`] if the result is empty, and
+    /// [`Error::QueryReturnedMoreThanOneRow`] if it has more than one row.
+    pub fn query_one<P, T, F>(&self, params: P, f: F) -> Result<T, Error>
+    where
+        P: Params,
+        F: FnOnce(&Row) -> Result<T, Error>,
+    {
+        let mut rows = self.query(params)?;
+        let row = rows.next().ok_or(Error::QueryReturnedNoRows)?;
+        if rows.next().is_some() {
+            return Err(Error::QueryReturnedMoreThanOneRow);
+        }
+        f(&row)
+    }
+}
+
+/// Rendering of Yes/No values as text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BooleanFormat {
+    /// `1` and `0`.
+    #[default]
+    Numbers,
+    /// `TRUE` and `FALSE`, requires libmdbsql 1.0.
+    Words,
+}
+
+/// Builder of a [`Connection`], setting options before the first query.
+///
+/// Every option applies to the connection it opens only, so connections to
+/// the same or other files can render values differently.
+///
+/// ```rust
+/// use mdbsql::{Connection, Error};
+///
+/// # fn main() -> Result<(), Error> {
+/// let conn = Connection::builder("./resource/test.mdb")
+///     .date_format("%Y-%m-%d %H:%M:%S")
+///     .open()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectionBuilder {
+    path: PathBuf,
+    options: OpenOptions,
+    no_memo: bool,
+    bind_size: Option<usize>,
+    backend: Option<String>,
+    date_format: Option<String>,
+    short_date_format: Option<String>,
+    boolean_format: BooleanFormat,
+    guid_format: GuidFormat,
+    resolve_links: bool,
+}
+
+impl ConnectionBuilder {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            options: OpenOptions::default(),
+            no_memo: false,
+            bind_size: None,
+            backend: None,
+            date_format: None,
+            short_date_format: None,
+            boolean_format: BooleanFormat::default(),
+            guid_format: GuidFormat::default(),
+            resolve_links: false,
+        }
+    }
+
+    /// Open the file for writing, read-only by default.
+    pub fn writable(mut self, writable: bool) -> Self {
+        self.options.writable = writable;
+        self
+    }
+
+    /// Open the file read-only, which is the default, undoing
+    /// [`ConnectionBuilder::writable`].
+    pub fn read_only(mut self) -> Self {
+        self.options.writable = false;
+        self
+    }
+
+    /// Skip reading Memo values.
+    ///
+    /// Memo columns are unbound from libmdbsql's results, so their values
+    /// read as empty text `""` rather than NULL. NULL values stay NULL.
+    pub fn no_memo(mut self, no_memo: bool) -> Self {
+        self.no_memo = no_memo;
+        self
+    }
+
+    /// Size of the buffer each value is rendered into.
+    pub fn bind_size(mut self, bind_size: usize) -> Self {
+        self.bind_size = Some(bind_size);
+        self
+    }
+
+    /// Backend used to render values, e.g. `access`, `sqlite` or `postgres`.
+    pub fn backend(mut self, backend: &str) -> Self {
+        self.backend = Some(backend.to_string());
+        self
+    }
+
+    /// strftime format of date/time values, requires libmdbsql 1.0.
+    pub fn date_format(mut self, format: &str) -> Self {
+        self.date_format = Some(format.to_string());
+        self
+    }
+
+    /// strftime format of date values without a time, requires libmdbsql 1.0.
+    pub fn short_date_format(mut self, format: &str) -> Self {
+        self.short_date_format = Some(format.to_string());
+        self
+    }
+
+    /// Rendering of Yes/No values.
+    pub fn boolean_format(mut self, format: BooleanFormat) -> Self {
+        self.boolean_format = format;
+        self
+    }
+
+    /// Charset of text in Jet3 (Access 97) files, e.g. `CP1252`, requires
+    /// libmdbsql 1.0.
+    pub fn jet3_charset(mut self, charset: &str) -> Self {
+        self.options.jet3_charset = Some(charset.to_string());
+        self
+    }
+
+    /// Text format of Replication ID values read as strings.
+    pub fn guid_format(mut self, format: GuidFormat) -> Self {
+        self.guid_format = format;
+        self
+    }
+
+    /// Run queries on linked tables in the databases holding them.
+    ///
+    /// Linked databases are opened with the same options, from the directory
+    /// of this database.
+    pub fn resolve_links(mut self, resolve_links: bool) -> Self {
+        self.resolve_links = resolve_links;
+        self
+    }
+
+    pub fn open(self) -> Result<Connection, Error> {
+        let mdb = Mdb::open_with(&self.path, &self.options)?;
+
+        if let Some(bind_size) = self.bind_size {
+            mdb.set_bind_size(bind_size);
+        }
+        if let Some(backend) = &self.backend {
+            mdb.set_default_backend(backend)?;
+        }
+        if let Some(format) = &self.date_format {
+            mdb.set_date_format(format)?;
+        }
+        if let Some(format) = &self.short_date_format {
+            mdb.set_short_date_format(format)?;
+        }
+        mdb.set_boolean_words(self.boolean_format == BooleanFormat::Words)?;
+
+        let mut conn = Connection::new(mdb, self.guid_format);
+        conn.no_memo = self.no_memo;
+        if self.resolve_links {
+            conn.links = self.open_links(&conn)?;
+        }
+        Ok(conn)
+    }
+
+    fn open_links(&self, conn: &Connection) -> Result<Links, Error> {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        let mut links = Links::default();
+        let mut paths: Vec<PathBuf> = vec![];
+
+        for entry in conn.catalog(false)? {
+            let link = match entry.link {
+                Some(link) => link,
+                None => continue,
+            };
+            let path = link.resolve(dir);
+            let i = match paths.iter().position(|p| *p == path) {
+                Some(i) => i,
+                None => {
+                    let mut builder = self.clone();
+                    builder.path = path.clone();
+                    builder.resolve_links = false;
+                    links.databases.push(builder.open()?);
+                    paths.push(path);
+                    paths.len() - 1
+                }
+            };
+            links
+                .tables
+                .insert(entry.name.to_lowercase(), (i, link.foreign_name));
+        }
+
+        Ok(links)
+    }
+}
+
+/// Position and name of the table a query selects from.
+fn from_table(query: &str) -> Option<(Range<usize>, String)> {
+    let bytes = query.as_bytes();
+    let mut after_from = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let token_end = match bytes[i] {
+            b'\'' | b'"' => {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                after_from = false;
+                continue;
+            }
+            b'[' => {
+                // skip the doubled brackets escaping `]`
+                let mut end = i + 1;
+                loop {
+                    match query[end..].find(']') {
+                        Some(close) if bytes.get(end + close + 1) == Some(&b']') => {
+                            end += close + 2
+                        }
+                        Some(close) => break end + close + 1,
+                        None => break bytes.len(),
+                    }
+                }
+            }
+            b if b.is_ascii_alphanumeric() || b == b'_' => {
+                let len = bytes[i..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                    .count();
+                i + len
+            }
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            _ => {
+                i += 1;
+                after_from = false;
+                continue;
+            }
+        };
+
+        let token = &query[start..token_end];
+        if after_from {
+            let name = match token.strip_prefix('[') {
+                Some(name) => name.strip_suffix(']').unwrap_or(name).replace("]]", "]"),
+                None => token.to_string(),
+            };
+            return Some((start..token_end, name));
+        }
+        after_from = token.eq_ignore_ascii_case("from");
+        i = token_end;
+    }
+
+    None
+}
+
+/// Lock on the database, also keeping queries from running.
+struct DbGuard<'conn> {
+    mdb: MutexGuard<'conn, Mdb>,
+    _query: MutexGuard<'conn, ()>,
+}
+
+impl<'conn> Deref for DbGuard<'conn> {
+    type Target = Mdb;
+
+    fn deref(&self) -> &Mdb {
+        &self.mdb
+    }
+}
+
 /// A handle for rows of query result.
-pub struct Rows<'mdb> {
-    mdb_guard: MutexGuard<'mdb, Mdb>,
-    columns: Vec<SqlColumn>,
+///
+/// Other queries wait until the rows are dropped. The database is only
+/// locked while a row is fetched, so rows can read OLE objects meanwhile.
+pub struct Rows<'conn> {
+    _query: MutexGuard<'conn, ()>,
+    db: Arc<Mutex<Mdb>>,
+    columns: Arc<Vec<SqlColumn>>,
+    guid_format: GuidFormat,
 }
 
-impl<'mdb> Rows<'mdb> {
+impl<'conn> Rows<'conn> {
+    fn new(guard: DbGuard<'conn>, db: Arc<Mutex<Mdb>>, guid_format: GuidFormat) -> Self {
+        let DbGuard { mdb, _query } = guard;
+        Self {
+            columns: Arc::new(mdb.sql_columns()),
+            _query,
+            db,
+            guid_format,
+        }
+    }
+
     pub fn columns(&self) -> &Vec<SqlColumn> {
         &self.columns
     }
-}
 
-impl<'mdb> From<MutexGuard<'mdb, Mdb>> for Rows<'mdb> {
-    fn from(mdb_guard: MutexGuard<'mdb, Mdb>) -> Self {
-        let columns = mdb_guard.sql_columns();
-        Self { mdb_guard, columns }
+    /// Deserialize each row into `T`.
+    ///
+    /// Structs are filled by matching column names to field names, ignoring case.
+    pub fn deserialize<T: serde::de::DeserializeOwned>(self) -> DeserializeRows<'conn, T> {
+        DeserializeRows {
+            rows: self,
+            _marker: PhantomData,
+        }
     }
 }
 
-impl<'mdb> Iterator for Rows<'mdb> {
+impl<'conn> Iterator for Rows<'conn> {
     type Item = Row;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.mdb_guard.sql_fetch_row() {
-            let values = self.mdb_guard.sql_bound_values();
-            Some(Row { values })
+        // A panic while the handle was locked leaves libmdb's cursor as it
+        // was, so the query goes on rather than ending as if it were done.
+        let mdb = self.db.lock().unwrap_or_else(PoisonError::into_inner);
+        if mdb.sql_fetch_row() {
+            let mut values = mdb.sql_bound_values(&self.columns);
+            drop(mdb);
+            // libmdb already renders the braced format
+            if self.guid_format != GuidFormat::Braced {
+                for (column, value) in self.columns.iter().zip(values.iter_mut()) {
+                    self.guid_format.apply(column, value);
+                }
+            }
+            Some(Row {
+                db: self.db.clone(),
+                columns: self.columns.clone(),
+                values,
+            })
         } else {
             None
         }
     }
 }
 
-impl<'mdb> Drop for Rows<'mdb> {
+impl<'conn> Drop for Rows<'conn> {
     fn drop(&mut self) {
         // reset query results when drop
-        self.mdb_guard.reset()
+        self.db
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .reset()
     }
 }
 
-/// Row of values.
+/// Iterator of rows deserialized into `T`.
+pub struct DeserializeRows<'conn, T> {
+    rows: Rows<'conn>,
+    _marker: PhantomData<T>,
+}
+
+impl<'conn, T: serde::de::DeserializeOwned> Iterator for DeserializeRows<'conn, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|row| row.deserialize())
+    }
+}
+
+/// Iterator of rows mapped by [`Statement::query_map`].
+pub struct MappedRows<'conn, F> {
+    rows: Rows<'conn>,
+    map: F,
+}
+
+impl<'conn, T, F> Iterator for MappedRows<'conn, F>
+where
+    F: FnMut(&Row) -> Result<T, Error>,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|row| (self.map)(&row))
+    }
+}
+
+/// Row of values, copied out of the query results.
 pub struct Row {
+    db: Arc<Mutex<Mdb>>,
+    columns: Arc<Vec<SqlColumn>>,
     values: Vec<SqlValue>,
 }
 
 impl Row {
-    /// Get value at index.
-    pub fn get<T: FromSql>(&self, idx: usize) -> Result<T, Error> {
-        if idx < self.values.len() {
-            T::column_result(self.values[idx].get()?)
+    /// Get value at index or column name.
+    pub fn get<T: FromSql>(&self, idx: impl RowIndex) -> Result<T, Error> {
+        T::from_sql(self.get_ref(idx)?)
+    }
+
+    /// Get value by column name, ignoring case.
+    pub fn get_by_name<T: FromSql>(&self, name: &str) -> Result<T, Error> {
+        self.get(name)
+    }
+
+    /// Get typed value at index or column name.
+    ///
+    /// MONEY and NUMERIC values are kept unscaled, so they convert exactly
+    /// with `TryFrom<&Value>`.
+    pub fn get_value(&self, idx: impl RowIndex) -> Result<Value, Error> {
+        self.get_ref(idx)?.value()
+    }
+
+    /// Get the bytes of a BINARY or OLE value at index or column name.
+    pub fn get_blob(&self, idx: impl RowIndex) -> Result<Vec<u8>, Error> {
+        self.get(idx)
+    }
+
+    /// Open an OLE object at index or column name for reading in chunks,
+    /// without loading it whole.
+    ///
+    /// NULL objects read as empty.
+    pub fn open_ole(&self, idx: impl RowIndex) -> Result<OleReader, Error> {
+        let idx = idx.idx(&self.columns)?;
+        let column = &self.columns[idx];
+        if column.col_type() != MDB_OLE as c_int {
+            return Err(Error::InvalidColumnType(column.name()));
+        }
+        Ok(OleReader {
+            db: self.db.clone(),
+            chunks: OleChunks::new(self.values[idx].data().unwrap_or_default()),
+            pos: 0,
+        })
+    }
+
+    /// Whether value at index or column name is NULL.
+    pub fn is_null(&self, idx: impl RowIndex) -> Result<bool, Error> {
+        Ok(self.get_ref(idx)?.is_null())
+    }
+
+    /// Get a reference to value at index or column name.
+    pub fn get_ref(&self, idx: impl RowIndex) -> Result<ValueRef<'_>, Error> {
+        let idx = idx.idx(&self.columns)?;
+        match (self.columns.get(idx), self.values.get(idx)) {
+            (Some(column), Some(value)) => Ok(ValueRef::new(&self.db, column, value)),
+            _ => Err(Error::InvalidRowIndex(idx)),
+        }
+    }
+
+    /// Columns of the row.
+    pub fn columns(&self) -> &Vec<SqlColumn> {
+        &self.columns
+    }
+
+    /// Deserialize the row into `T`.
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T, Error> {
+        T::deserialize(RowDeserializer::new(self))
+    }
+}
+
+/// Reader of an OLE object, opened with [`Row::open_ole`].
+///
+/// The database is locked for each chunk read.
+pub struct OleReader {
+    db: Arc<Mutex<Mdb>>,
+    chunks: OleChunks,
+    /// Position within the current chunk.
+    pos: usize,
+}
+
+impl Read for OleReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.chunks.chunk().len() {
+            let mdb = self
+                .db
+                .lock()
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            mdb.ole_read_chunk(&mut self.chunks);
+            self.pos = 0;
+        }
+        let chunk = &self.chunks.chunk()[self.pos..];
+        let len = chunk.len().min(buf.len());
+        buf[..len].copy_from_slice(&chunk[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// A trait for types that can index into a row.
+pub trait RowIndex {
+    /// Position of the column within `columns`.
+    fn idx(&self, columns: &[SqlColumn]) -> Result<usize, Error>;
+}
+
+impl RowIndex for usize {
+    fn idx(&self, columns: &[SqlColumn]) -> Result<usize, Error> {
+        if *self < columns.len() {
+            Ok(*self)
         } else {
-            Err(Error::InvalidRowIndex(idx))
+            Err(Error::InvalidRowIndex(*self))
         }
     }
 }
 
+impl RowIndex for &str {
+    fn idx(&self, columns: &[SqlColumn]) -> Result<usize, Error> {
+        // Access treats column names case-insensitively.
+        columns
+            .iter()
+            .position(|c| c.name().eq_ignore_ascii_case(self))
+            .ok_or_else(|| Error::InvalidColumnName(self.to_string()))
+    }
+}
+
 pub trait FromSql: Sized {
     /// Converts SQL value into Rust value.
     fn column_result(value: &str) -> Result<Self, Error>;
+
+    /// Converts a cell of a row into Rust value.
+    ///
+    /// Defaults to converting the text of the cell with [`FromSql::column_result`].
+    fn from_sql(value: ValueRef) -> Result<Self, Error> {
+        Self::column_result(value.as_str()?)
+    }
 }
 
+/// Text is converted with `serde_plain`, and cells are deserialized from
+/// their typed value, so any type implementing `Deserialize` can be read.
+///
+/// Dates and decimals, which serde has no type for, are converted from the
+/// cell's [`Value`] instead.
 impl<T> FromSql for T
 where
-    T: serde::de::DeserializeOwned,
+    T: serde::de::DeserializeOwned + 'static,
 {
     fn column_result(value: &str) -> Result<T, Error> {
         Ok(serde_plain::from_str(value)?)
     }
+
+    fn from_sql(value: ValueRef) -> Result<T, Error> {
+        match from_value(&value) {
+            Some(result) => result,
+            None => T::deserialize(ValueDeserializer::new(value)),
+        }
+    }
+}
+
+type FromValue = fn(&ValueRef) -> Result<Box<dyn Any>, Error>;
+
+/// Converts the cell with `T`'s `TryFrom<&Value>`, if it is one of the
+/// types read that way.
+fn from_value<T: 'static>(value: &ValueRef) -> Option<Result<T, Error>> {
+    let converters: &[(TypeId, FromValue)] = &[
+        #[cfg(feature = "chrono")]
+        exact::<::chrono::NaiveDateTime>(),
+        #[cfg(feature = "chrono")]
+        optional::<::chrono::NaiveDateTime>(),
+        #[cfg(feature = "chrono")]
+        exact::<::chrono::NaiveDate>(),
+        #[cfg(feature = "chrono")]
+        optional::<::chrono::NaiveDate>(),
+        #[cfg(feature = "time")]
+        exact::<::time::PrimitiveDateTime>(),
+        #[cfg(feature = "time")]
+        optional::<::time::PrimitiveDateTime>(),
+        #[cfg(feature = "time")]
+        exact::<::time::Date>(),
+        #[cfg(feature = "time")]
+        optional::<::time::Date>(),
+        #[cfg(feature = "rust_decimal")]
+        exact::<::rust_decimal::Decimal>(),
+        #[cfg(feature = "rust_decimal")]
+        optional::<::rust_decimal::Decimal>(),
+    ];
+    let (_, convert) = converters.iter().find(|(id, _)| *id == TypeId::of::<T>())?;
+    Some(convert(value).map(|v| *v.downcast().expect("converted to T")))
+}
+
+#[cfg_attr(
+    not(any(feature = "chrono", feature = "time", feature = "rust_decimal")),
+    allow(dead_code)
+)]
+fn exact<U>() -> (TypeId, FromValue)
+where
+    U: for<'a> TryFrom<&'a Value, Error = Error> + 'static,
+{
+    (TypeId::of::<U>(), |value| {
+        Ok(Box::new(U::try_from(&value.value()?)?))
+    })
+}
+
+/// `Option<U>`, which is `None` for NULL.
+#[cfg_attr(
+    not(any(feature = "chrono", feature = "time", feature = "rust_decimal")),
+    allow(dead_code)
+)]
+fn optional<U>() -> (TypeId, FromValue)
+where
+    U: for<'a> TryFrom<&'a Value, Error = Error> + 'static,
+{
+    (TypeId::of::<Option<U>>(), |value| match value.value()? {
+        Value::Null => Ok(Box::new(None::<U>)),
+        v => Ok(Box::new(Some(U::try_from(&v)?))),
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::catalog::{ColumnType, IndexColumn, Link, SortOrder};
+    use crate::saved_query::QueryType;
+    use serde::Deserialize;
     use std::sync::Arc;
     use std::thread;
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, PartialEq, Deserialize)]
     struct Table1 {
         id: u64,
         a: String,
@@ -152,6 +1113,674 @@ mod test {
         );
     }
 
+    #[test]
+    fn from_sql_deserialize() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Name {
+            Foo,
+            #[serde(rename = "fOO")]
+            LowerFoo,
+        }
+
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let names: Vec<Name> = conn
+            .prepare("select A from Table1")
+            .unwrap()
+            .map(|r| r.get(0).unwrap())
+            .collect();
+        assert_eq!(names, vec![Name::Foo, Name::LowerFoo]);
+    }
+
+    #[test]
+    fn builder() {
+        let conn = Connection::builder("resource/test.mdb")
+            .date_format("%Y-%m-%d")
+            .open()
+            .unwrap();
+        let row = conn
+            .prepare("select D from Table1 where ID=1")
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(row.get::<String>("D").unwrap(), "2000-01-01");
+
+        // Options apply to the connection they open only.
+        let other = Connection::builder("resource/test.mdb")
+            .backend("sqlite")
+            .boolean_format(BooleanFormat::Words)
+            .no_memo(true)
+            .writable(true)
+            .read_only()
+            .open()
+            .unwrap();
+        let query = "select D, E, F from Table1 where ID=1";
+        let row = other.prepare(query).unwrap().next().unwrap();
+        assert_eq!(row.get::<String>("D").unwrap(), "01/01/00 00:00:00");
+        assert_eq!(row.get::<String>("E").unwrap(), "TRUE");
+        assert_eq!(row.get::<String>("F").unwrap(), "");
+        assert_eq!(row.get::<Option<String>>("F").unwrap(), Some(String::new()));
+        let row = conn.prepare(query).unwrap().next().unwrap();
+        assert_eq!(row.get::<String>("D").unwrap(), "2000-01-01");
+        assert_eq!(row.get::<String>("E").unwrap(), "1");
+        assert_eq!(
+            row.get::<String>("F").unwrap(),
+            "<div><font face=Calibri>FooBar</font></div>"
+        );
+
+        assert!(matches!(
+            Connection::builder("resource/test.mdb")
+                .backend("nosql")
+                .open(),
+            Err(Error::MdbSqlError(_))
+        ));
+    }
+
+    #[test]
+    fn owned_row() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select A from Table1 where ID=1")
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_send(&row);
+
+        // A row holds no lock, so other queries run while it is alive.
+        assert_eq!(conn.prepare("select * from Table1").unwrap().count(), 2);
+        assert_eq!(row.get::<String>(0).unwrap(), "Foo");
+    }
+
+    #[test]
+    fn open_bytes() {
+        let bytes = std::fs::read("resource/test.mdb").unwrap();
+        let conn = Connection::open_reader(io::Cursor::new(bytes.clone())).unwrap();
+        let row = conn
+            .prepare("select A from Table1")
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(row.get::<String>(0).unwrap(), "Foo");
+
+        let conn = Connection::open_bytes(bytes).unwrap();
+        assert_eq!(conn.prepare("select * from Table1").unwrap().count(), 2);
+
+        assert!(matches!(
+            Connection::open_bytes(b"not a database".to_vec()),
+            Err(Error::InvalidMdbFile(_))
+        ));
+    }
+
+    #[test]
+    fn catalog() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        assert!(conn.tables().unwrap().contains(&"Table1".to_string()));
+
+        let catalog = conn.catalog(false).unwrap();
+        assert!(catalog.iter().all(|e| !e.system));
+        assert!(catalog
+            .iter()
+            .any(|e| e.name == "Table1" && e.object_type == ObjectType::Table));
+
+        let catalog = conn.catalog(true).unwrap();
+        assert!(catalog
+            .iter()
+            .any(|e| e.name == "MSysObjects" && e.object_type == ObjectType::Table && e.system));
+    }
+
+    #[test]
+    fn table_info() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let columns = conn.table_info("Table1").unwrap();
+
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["ID", "A", "B", "C", "D", "E", "F"]);
+        let types: Vec<ColumnType> = columns.iter().map(|c| c.column_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                ColumnType::LongInt,
+                ColumnType::Text,
+                ColumnType::LongInt,
+                ColumnType::Money,
+                ColumnType::DateTime,
+                ColumnType::Bool,
+                ColumnType::Memo
+            ]
+        );
+
+        assert!(columns[0].auto_number && columns[0].fixed);
+        assert!(!columns[1].auto_number && !columns[1].fixed);
+        assert!(!columns[5].nullable);
+
+        assert!(matches!(
+            conn.table_info("NoSuchTable"),
+            Err(Error::MdbSqlError(_))
+        ));
+    }
+
+    #[test]
+    fn indexes() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let indexes = conn.indexes("Table1").unwrap();
+
+        let primary_key = indexes.iter().find(|i| i.primary_key).unwrap();
+        assert_eq!(primary_key.name, "PrimaryKey");
+        assert!(primary_key.unique);
+        assert_eq!(
+            primary_key.columns,
+            vec![IndexColumn {
+                name: "ID".to_string(),
+                order: SortOrder::Ascending
+            }]
+        );
+    }
+
+    #[test]
+    fn properties() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let columns = conn.column_properties("Table1").unwrap();
+
+        let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["ID", "A", "B", "C", "D", "E", "F"]);
+        assert_eq!(
+            columns[3].1.format(),
+            Some("\"NT$\"#,##0.00;\"-NT$\"#,##0.00")
+        );
+        assert_eq!(columns[5].1.format(), Some("Yes/No"));
+        assert!(!columns[1].1.required());
+
+        assert!(conn.table_properties("Table1").is_ok());
+    }
+
+    #[test]
+    fn table_stats() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let stats = conn.table_stats("Table1").unwrap();
+        assert_eq!(stats.name, "Table1");
+        assert_eq!(stats.num_rows, 2);
+        assert!(stats.data_pages >= 1);
+        assert!(stats.index_pages >= 1);
+        assert_eq!(
+            stats.size,
+            (stats.data_pages + stats.index_pages) as u64 * 4096
+        );
+        assert!(conn.table_stats("Table0").is_err());
+
+        let db = conn.database_stats().unwrap();
+        assert_eq!(db.page_size, 4096);
+        let file_size = std::fs::metadata("resource/test.mdb").unwrap().len();
+        assert!(db.pages as u64 * db.page_size as u64 <= file_size);
+        assert_eq!(db.size, db.pages as u64 * db.page_size as u64);
+        assert!(db.data_pages > 0);
+        assert!(db.data_pages >= stats.data_pages);
+        assert!(db.index_pages >= stats.index_pages);
+        assert_eq!(db.tables, vec![stats]);
+    }
+
+    #[test]
+    fn linked_tables() {
+        let conn = Connection::builder("resource/test.mdb")
+            .resolve_links(true)
+            .open()
+            .unwrap();
+        assert!(conn.catalog(true).unwrap().iter().all(|e| e.link.is_none()));
+        assert_eq!(conn.prepare("select * from Table1").unwrap().count(), 2);
+
+        // Linked1 of frontend.mdb links to Table1 of test.mdb
+        let conn = Connection::open("resource/frontend.mdb").unwrap();
+        let linked = conn
+            .catalog(false)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.name == "Linked1")
+            .unwrap();
+        assert_eq!(linked.object_type, ObjectType::LinkedTable);
+        assert_eq!(
+            linked.link,
+            Some(Link {
+                database: "C:\\Data\\test.mdb".to_string(),
+                foreign_name: "Table1".to_string(),
+            })
+        );
+
+        let conn = Connection::builder("resource/frontend.mdb")
+            .resolve_links(true)
+            .open()
+            .unwrap();
+        let a: Vec<String> = conn
+            .prepare("select A from Linked1")
+            .unwrap()
+            .map(|row| row.get(0))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(a, vec!["Foo", "fOO"]);
+        let stmt = conn
+            .statement("select * from Linked1 where ID = ?")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 7);
+        let a: String = stmt
+            .query_row(crate::params![2], |row| row.get("A"))
+            .unwrap();
+        assert_eq!(a, "fOO");
+    }
+
+    #[test]
+    fn query() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let ids: Vec<i32> = conn
+            .query("select ID from Table1 where A = ?", crate::params!["Foo"])
+            .unwrap()
+            .map(|r| r.get(0).unwrap())
+            .collect();
+        assert_eq!(ids, vec![1]);
+
+        let ids: Vec<i32> = conn
+            .query(
+                "select ID from Table1 where ID > :id",
+                crate::named_params! { ":id": 0 },
+            )
+            .unwrap()
+            .map(|r| r.get(0).unwrap())
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        assert!(matches!(
+            conn.query("select ID from Table1 where ID = ?", ()),
+            Err(Error::InvalidParameterCount(1, 0))
+        ));
+    }
+
+    #[test]
+    fn statement() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let stmt = conn.statement("select ID, A from Table1").unwrap();
+        assert_eq!(stmt.column_names(), vec!["ID", "A"]);
+        let ids: Vec<i32> = stmt
+            .query_map((), |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, vec![1, 2]);
+
+        let stmt = conn.statement("select A from Table1 where ID = ?").unwrap();
+        assert_eq!(stmt.sql(), "select A from Table1 where ID = ?");
+        assert_eq!(stmt.column_names(), vec!["A"]);
+        for (id, a) in [(1, "Foo"), (2, "fOO")] {
+            let value: String = stmt
+                .query_row(crate::params![id], |row| row.get(0))
+                .unwrap();
+            assert_eq!(value, a);
+        }
+        assert!(matches!(
+            stmt.query_row(crate::params![3], |row| row.get::<String>(0)),
+            Err(Error::QueryReturnedNoRows)
+        ));
+
+        let stmt = conn.statement("select * from Table1 where A = :a").unwrap();
+        assert_eq!(stmt.column_count(), 7);
+        assert!(conn.statement("select * from Table0").is_err());
+        assert!(conn.statement("select G from Table1").is_err());
+    }
+
+    #[test]
+    fn query_row() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let a: String = conn
+            .query_row(
+                "select A from Table1 where ID = ?",
+                crate::params![2],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(a, "fOO");
+        let id: i32 = conn
+            .query_row("select ID from Table1", (), |row| row.get(0))
+            .unwrap();
+        assert_eq!(id, 1);
+        assert!(matches!(
+            conn.query_row("select ID from Table1 where ID = 3", (), |row| row
+                .get::<i32>(0)),
+            Err(Error::QueryReturnedNoRows)
+        ));
+
+        let id: i32 = conn
+            .query_one(
+                "select ID from Table1 where ID = :id",
+                crate::named_params! { ":id": 1 },
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(id, 1);
+        assert!(matches!(
+            conn.query_one("select ID from Table1", (), |row| row.get::<i32>(0)),
+            Err(Error::QueryReturnedMoreThanOneRow)
+        ));
+    }
+
+    #[test]
+    fn select() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let result = conn
+            .select("SELECT ID, A FROM Table1 ORDER BY A DESC, ID", ())
+            .unwrap();
+        assert_eq!(result.columns, vec!["ID", "A"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![Value::LongInt(1), Value::Text("Foo".to_string())],
+                vec![Value::LongInt(2), Value::Text("fOO".to_string())],
+            ]
+        );
+
+        let result = conn
+            .select(
+                "SELECT DISTINCT ID, COUNT(*), SUM(C) AS Total FROM Table1 WHERE ID > ? \
+                 GROUP BY ID ORDER BY Total DESC",
+                crate::params![0],
+            )
+            .unwrap();
+        assert_eq!(result.columns, vec!["ID", "COUNT(*)", "Total"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![Value::LongInt(2), Value::LongInt(1), Value::Money(990000)],
+                vec![Value::LongInt(1), Value::LongInt(1), Value::Money(10000)],
+            ]
+        );
+
+        assert!(matches!(
+            conn.select("SELECT MEDIAN(ID) FROM Table1", ()),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn join() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let result = conn
+            .select(
+                "SELECT a.ID, b.A FROM Table1 a LEFT JOIN Table1 b ON a.ID = b.ID AND b.A = ? \
+                 ORDER BY a.ID",
+                crate::params!["Foo"],
+            )
+            .unwrap();
+        assert_eq!(result.columns, vec!["ID", "A"]);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![Value::LongInt(1), Value::Text("Foo".to_string())],
+                vec![Value::LongInt(2), Value::Null],
+            ]
+        );
+
+        let result = conn
+            .select(
+                "SELECT COUNT(*) FROM Table1 a INNER JOIN Table1 b ON a.A = b.A",
+                (),
+            )
+            .unwrap();
+        assert_eq!(result.rows, vec![vec![Value::LongInt(4)]]);
+
+        let query = "SELECT a.ID FROM Table1 a INNER JOIN Table1 b ON a.ID = b.ID";
+        assert!(matches!(conn.prepare(query), Err(Error::InvalidQuery(_))));
+        let stmt = conn.statement(query).unwrap();
+        assert!(matches!(stmt.query(()), Err(Error::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn query_table() {
+        let query = "SELECT A, 'from x' FROM [Order Lines] WHERE B > 1";
+        let (range, name) = from_table(query).unwrap();
+        assert_eq!(name, "Order Lines");
+        assert_eq!(&query[range], "[Order Lines]");
+        assert_eq!(
+            from_table("select * from Table1").map(|(_, name)| name),
+            Some("Table1".to_string())
+        );
+        assert_eq!(from_table("select 'from Table1'"), None);
+
+        let query = "SELECT * FROM [a]]b] WHERE ID = 1";
+        let (range, name) = from_table(query).unwrap();
+        assert_eq!(name, "a]b");
+        assert_eq!(&query[range], "[a]]b]");
+    }
+
+    #[test]
+    fn relationships() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let relationships = conn.relationships().unwrap();
+        assert_eq!(relationships.len(), 2);
+
+        let groups = relationships
+            .iter()
+            .find(|r| r.table == "MSysNavPaneGroups")
+            .unwrap();
+        assert_eq!(groups.referenced_table, "MSysNavPaneGroupCategories");
+        assert_eq!(groups.columns.len(), 1);
+
+        let tables: Vec<String> = [
+            "MSysNavPaneGroupToObjects",
+            "MSysNavPaneGroups",
+            "MSysNavPaneGroupCategories",
+        ]
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+        assert_eq!(
+            crate::dependency_order(&tables, &relationships).unwrap(),
+            vec![
+                "MSysNavPaneGroupCategories",
+                "MSysNavPaneGroups",
+                "MSysNavPaneGroupToObjects"
+            ]
+        );
+    }
+
+    #[test]
+    fn saved_queries() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        assert_eq!(conn.saved_queries().unwrap(), vec![]);
+
+        let conn = Connection::open("resource/queries.mdb").unwrap();
+        assert_eq!(
+            conn.saved_queries().unwrap(),
+            vec![SavedQuery {
+                name: "Query1".to_string(),
+                query_type: QueryType::Select,
+                parameters: vec![],
+                sql: "SELECT a.ID, b.A\n\
+                      FROM Table1 AS a INNER JOIN Table1 AS b ON a.ID = b.ID\n\
+                      WHERE a.C > 100\n\
+                      ORDER BY a.ID DESC;"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn typed_values() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select * from Table1 where ID=1")
+            .unwrap()
+            .next()
+            .unwrap();
+
+        assert_eq!(row.get_value(0).unwrap(), Value::LongInt(1));
+        assert_eq!(row.get_value(1).unwrap(), Value::Text("Foo".to_string()));
+        assert_eq!(row.get_value(2).unwrap(), Value::LongInt(1));
+        assert_eq!(row.get_value(3).unwrap(), Value::Money(10000));
+        assert_eq!(row.get_value(4).unwrap(), Value::DateTime(36526.0));
+        assert_eq!(row.get_value(5).unwrap(), Value::Bool(true));
+        assert_eq!(
+            row.get_value(6).unwrap(),
+            Value::Memo("<div><font face=Calibri>FooBar</font></div>".to_string())
+        );
+        assert!(matches!(row.get_value(7), Err(Error::InvalidRowIndex(7))));
+    }
+
+    #[test]
+    fn index_scan() {
+        // libmdb reads MDBOPTS once per process, so the test runs again in a
+        // process of its own with index scans enabled.
+        if std::env::var_os("MDBOPTS").is_none() {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "mdbsql::test::index_scan"])
+                .env("MDBOPTS", "use_index")
+                .status()
+                .unwrap();
+            assert!(status.success());
+            return;
+        }
+
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select * from Table1 where ID = 2")
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(row.get_value(0).unwrap(), Value::LongInt(2));
+        assert_eq!(row.get_value(1).unwrap(), Value::Text("fOO".to_string()));
+        assert_eq!(row.get_value(3).unwrap(), Value::Money(990000));
+        assert_eq!(row.get_value(4).unwrap(), Value::DateTime(36161.0));
+    }
+
+    #[test]
+    fn nullable_values() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select ID, A from Table1 where ID=1")
+            .unwrap()
+            .next()
+            .unwrap();
+
+        assert!(!row.is_null(1).unwrap());
+        assert_eq!(
+            row.get::<Option<String>>(1).unwrap(),
+            Some("Foo".to_string())
+        );
+        assert_eq!(row.get::<Option<u32>>(0).unwrap(), Some(1));
+        assert!(matches!(row.is_null(2), Err(Error::InvalidRowIndex(2))));
+    }
+
+    #[test]
+    fn column_names() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select ID, A from Table1 where ID=1")
+            .unwrap()
+            .next()
+            .unwrap();
+
+        assert_eq!(row.get::<u32>("ID").unwrap(), 1);
+        assert_eq!(row.get::<String>("a").unwrap(), "Foo");
+        assert_eq!(row.get_by_name::<u32>("id").unwrap(), 1);
+        assert_eq!(row.get_value("A").unwrap(), row.get_value(1).unwrap());
+        assert!(matches!(
+            row.get::<u32>("B"),
+            Err(Error::InvalidColumnName(name)) if name == "B"
+        ));
+    }
+
+    #[test]
+    fn blob() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select LvProp from MSysObjects where Name='Table1'")
+            .unwrap()
+            .next()
+            .unwrap();
+
+        let blob = row.get_blob(0).unwrap();
+        // Jet4 property block
+        assert!(blob.starts_with(b"MR2\0"));
+        assert_eq!(row.get::<Vec<u8>>("LvProp").unwrap(), blob);
+        assert_eq!(row.get_value(0).unwrap(), Value::Ole(blob));
+    }
+
+    #[test]
+    fn open_ole() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let row = conn
+            .prepare("select Name, LvProp from MSysObjects where Name='Table1'")
+            .unwrap()
+            .next()
+            .unwrap();
+
+        let mut reader = row.open_ole("LvProp").unwrap();
+        let mut data = vec![];
+        let mut buf = [0u8; 7];
+        loop {
+            match reader.read(&mut buf).unwrap() {
+                0 => break,
+                len => data.extend_from_slice(&buf[..len]),
+            }
+        }
+        assert_eq!(data, row.get_blob("LvProp").unwrap());
+        assert!(matches!(
+            row.open_ole("Name"),
+            Err(Error::InvalidColumnType(_))
+        ));
+    }
+
+    #[test]
+    fn deserialize() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let tables: Vec<Table1> = conn
+            .prepare("select * from Table1")
+            .unwrap()
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(
+            tables[0],
+            Table1 {
+                id: 1,
+                a: "Foo".to_string(),
+                b: 1,
+                c: 1.0000,
+                d: "01/01/00 00:00:00".to_string(),
+                e: 1,
+                f: "<div><font face=Calibri>FooBar</font></div>".to_string()
+            }
+        );
+
+        let tuples: Vec<(u32, Option<String>)> = conn
+            .prepare("select ID, A from Table1 where ID=1")
+            .unwrap()
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tuples, vec![(1, Some("Foo".to_string()))]);
+
+        // whole Currency values are integers too
+        let amounts: Vec<(i64, f64)> = conn
+            .prepare("select C from Table1")
+            .unwrap()
+            .map(|row| Ok((row.get(0)?, row.get(0)?)))
+            .collect::<Result<_, Error>>()
+            .unwrap();
+        assert_eq!(amounts, vec![(1, 1.0), (99, 99.0)]);
+    }
+
+    #[test]
+    fn poisoned_lock() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let rows = conn.prepare("select ID from Table1").unwrap();
+        let db = conn.db.clone();
+        let _ = thread::spawn(move || {
+            let _mdb = db.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join();
+        assert!(conn.db.is_poisoned());
+        assert_eq!(rows.count(), 2);
+    }
+
     #[test]
     fn multithreading() {
         let conn = Arc::new(Connection::open("resource/test.mdb").unwrap());
@@ -0,0 +1,314 @@
+//! Embedded files of OLE Object columns.
+//!
+//! Access wraps OLE objects in a header naming their class, followed by an OLE
+//! stream whose data block holds the object. Files inserted as "Package" carry
+//! their original name and content, and "Bitmap Image" objects hold a BMP file.
+
+use crate::error::Error;
+use crate::mdbsql::FromSql;
+use crate::value::ValueRef;
+
+const OLE_SIGNATURE: u16 = 0x1c15;
+const PACKAGE_SIGNATURE: u16 = 0x0002;
+const PACKAGE_EMBEDDED: u32 = 0x0003_0000;
+
+/// An OLE object unwrapped from its Access headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OleObject {
+    /// Class of the object, e.g. `Package` or `PBrush`.
+    pub class_name: String,
+    /// Original file name, for embedded packages.
+    pub file_name: Option<String>,
+    /// MIME type guessed from the content and file name.
+    pub mime_type: &'static str,
+    /// Content of the embedded file.
+    pub data: Vec<u8>,
+}
+
+impl OleObject {
+    /// Parse the bytes of an OLE Object cell.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut header = Cursor::new(bytes);
+        if header.u16()? != OLE_SIGNATURE {
+            return Err(Error::InvalidOleObject("missing OLE header".to_string()));
+        }
+        let header_size = header.u16()? as usize;
+        header.skip(4)?;
+        let _name_len = header.u16()?;
+        let class_len = header.u16()? as usize;
+        let _name_offset = header.u16()?;
+        let class_offset = header.u16()? as usize;
+        let class_name = latin1(trim_nul(Cursor::new(bytes).slice(class_offset, class_len)?));
+
+        // OLE stream: version, format, class name, and the data block
+        let mut stream = Cursor::new(bytes);
+        stream.skip(header_size + 8)?;
+        let type_len = stream.u32()? as usize;
+        let type_name = latin1(trim_nul(stream.take(type_len)?));
+        stream.skip(8)?;
+        let data_len = stream.u32()? as usize;
+        let block = stream.take(data_len)?;
+
+        let (file_name, data) = if type_name.eq_ignore_ascii_case("Package") {
+            parse_package(block)?
+        } else {
+            (None, block.to_vec())
+        };
+
+        Ok(Self {
+            class_name: if class_name.is_empty() {
+                type_name
+            } else {
+                class_name
+            },
+            mime_type: guess_mime_type(&data, file_name.as_deref()),
+            file_name,
+            data,
+        })
+    }
+}
+
+impl FromSql for OleObject {
+    fn column_result(_value: &str) -> Result<Self, Error> {
+        Err(Error::InvalidOleObject(
+            "OLE objects are not read from text".to_string(),
+        ))
+    }
+
+    fn from_sql(value: ValueRef) -> Result<Self, Error> {
+        Self::parse(&Vec::<u8>::from_sql(value)?)
+    }
+}
+
+/// Parse the data block of a Package into the file name and content.
+fn parse_package(block: &[u8]) -> Result<(Option<String>, Vec<u8>), Error> {
+    let mut package = Cursor::new(block);
+    if package.u16()? != PACKAGE_SIGNATURE {
+        return Err(Error::InvalidOleObject(
+            "missing package header".to_string(),
+        ));
+    }
+    let label = latin1(package.c_str()?);
+    let path = latin1(package.c_str()?);
+    if package.u32()? != PACKAGE_EMBEDDED {
+        return Err(Error::InvalidOleObject(
+            "linked packages have no content".to_string(),
+        ));
+    }
+    let temp_path_len = package.u32()? as usize;
+    package.skip(temp_path_len)?;
+    let data_len = package.u32()? as usize;
+    let data = package.take(data_len)?.to_vec();
+
+    // Prefer the file name of the source path over the label.
+    let file_name = path
+        .rsplit(['\\', '/'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .or_else(|| Some(label).filter(|label| !label.is_empty()));
+
+    Ok((file_name, data))
+}
+
+/// Guess the MIME type of a file from its magic number, then its extension.
+fn guess_mime_type(data: &[u8], file_name: Option<&str>) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"BM", "image/bmp"),
+        (b"II*\0", "image/tiff"),
+        (b"MM\0*", "image/tiff"),
+        (b"%PDF-", "application/pdf"),
+        (b"{\\rtf", "application/rtf"),
+        (b"PK\x03\x04", "application/zip"),
+        (
+            b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1",
+            "application/x-ole-storage",
+        ),
+    ];
+    const EXTENSIONS: &[(&str, &str)] = &[
+        ("txt", "text/plain"),
+        ("csv", "text/csv"),
+        ("htm", "text/html"),
+        ("html", "text/html"),
+        ("xml", "application/xml"),
+        ("doc", "application/msword"),
+        ("xls", "application/vnd.ms-excel"),
+        ("ppt", "application/vnd.ms-powerpoint"),
+        (
+            "docx",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        ),
+        (
+            "xlsx",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        ),
+        (
+            "pptx",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        ),
+    ];
+
+    // Office documents are zip or OLE storage files, so their extension is
+    // more specific than their magic number.
+    let by_extension = file_name
+        .and_then(|name| name.rsplit_once('.'))
+        .and_then(|(_, ext)| {
+            EXTENSIONS
+                .iter()
+                .find(|(e, _)| e.eq_ignore_ascii_case(ext))
+                .map(|(_, mime)| *mime)
+        });
+
+    by_extension
+        .or_else(|| {
+            MAGIC
+                .iter()
+                .find(|(magic, _)| data.starts_with(magic))
+                .map(|(_, mime)| *mime)
+        })
+        .unwrap_or("application/octet-stream")
+}
+
+fn trim_nul(bytes: &[u8]) -> &[u8] {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    &bytes[..end]
+}
+
+/// Decode an ANSI string of the header.
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+/// Little-endian reader over the bytes of an OLE object.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn slice(&self, start: usize, len: usize) -> Result<&'a [u8], Error> {
+        start
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(start..end))
+            .ok_or_else(|| Error::InvalidOleObject("truncated object".to_string()))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = self.slice(self.pos, len)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), Error> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// NUL-terminated string.
+    fn c_str(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.bytes[self.pos.min(self.bytes.len())..]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| Error::InvalidOleObject("truncated object".to_string()))?;
+        let bytes = self.take(len)?;
+        self.skip(1)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Wrap `block` like Access does for objects of class `class_name`.
+    fn ole_object(class_name: &str, block: &[u8]) -> Vec<u8> {
+        let name = b"Packager Shell Object\0";
+        let class = [class_name.as_bytes(), b"\0"].concat();
+        let header_size = 20 + name.len() + class.len();
+
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&OLE_SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&(header_size as u16).to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(class.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&20u16.to_le_bytes());
+        bytes.extend_from_slice(&((20 + name.len()) as u16).to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        bytes.extend_from_slice(name);
+        bytes.extend_from_slice(&class);
+
+        bytes.extend_from_slice(&0x0501u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&(class.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&class);
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&(block.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(block);
+        bytes
+    }
+
+    #[test]
+    fn package() {
+        let mut block = vec![];
+        block.extend_from_slice(&PACKAGE_SIGNATURE.to_le_bytes());
+        block.extend_from_slice(b"report.pdf\0C:\\Users\\foo\\report.pdf\0");
+        block.extend_from_slice(&PACKAGE_EMBEDDED.to_le_bytes());
+        let temp_path = b"C:\\Temp\\report.pdf\0";
+        block.extend_from_slice(&(temp_path.len() as u32).to_le_bytes());
+        block.extend_from_slice(temp_path);
+        block.extend_from_slice(&9u32.to_le_bytes());
+        block.extend_from_slice(b"%PDF-1.4\n");
+
+        let object = OleObject::parse(&ole_object("Package", &block)).unwrap();
+        assert_eq!(
+            object,
+            OleObject {
+                class_name: "Package".to_string(),
+                file_name: Some("report.pdf".to_string()),
+                mime_type: "application/pdf",
+                data: b"%PDF-1.4\n".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn bitmap_image() {
+        let bitmap = b"BM\x1e\0\0\0".to_vec();
+        let object = OleObject::parse(&ole_object("PBrush", &bitmap)).unwrap();
+        assert_eq!(object.class_name, "PBrush");
+        assert_eq!(object.file_name, None);
+        assert_eq!(object.mime_type, "image/bmp");
+        assert_eq!(object.data, bitmap);
+    }
+
+    #[test]
+    fn invalid_object() {
+        assert!(matches!(
+            OleObject::parse(b"MR2\0"),
+            Err(Error::InvalidOleObject(_))
+        ));
+        let truncated = ole_object("PBrush", b"BM");
+        assert!(matches!(
+            OleObject::parse(&truncated[..truncated.len() - 1]),
+            Err(Error::InvalidOleObject(_))
+        ));
+    }
+}
@@ -0,0 +1,386 @@
+//! Parameters of queries, rendered as mdb-sql literals.
+//!
+//! libmdbsql has no prepared statements, so parameters are substituted into
+//! the query text before it is parsed. Placeholders are `?` for positional
+//! parameters and `:name` for named ones, and are not replaced inside string
+//! literals or bracketed names.
+
+use crate::error::Error;
+
+/// Conversion of a Rust value into an mdb-sql literal.
+pub trait ToSql {
+    /// Renders the value as a literal of the mdb-sql dialect.
+    fn to_sql(&self) -> Result<String, Error>;
+}
+
+/// Quote a string literal, doubling single quotes.
+pub(crate) fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+impl ToSql for str {
+    fn to_sql(&self) -> Result<String, Error> {
+        Ok(quote(self))
+    }
+}
+
+impl ToSql for String {
+    fn to_sql(&self) -> Result<String, Error> {
+        self.as_str().to_sql()
+    }
+}
+
+impl ToSql for char {
+    fn to_sql(&self) -> Result<String, Error> {
+        self.to_string().to_sql()
+    }
+}
+
+/// Yes/No values are compared as `1` and `0`.
+impl ToSql for bool {
+    fn to_sql(&self) -> Result<String, Error> {
+        Ok(if *self { "1" } else { "0" }.to_string())
+    }
+}
+
+macro_rules! to_sql_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ToSql for $t {
+                fn to_sql(&self) -> Result<String, Error> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+to_sql_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! to_sql_float {
+    ($($t:ty),*) => {
+        $(
+            impl ToSql for $t {
+                /// Renders finite numbers without exponent, which libmdbsql
+                /// does not parse.
+                fn to_sql(&self) -> Result<String, Error> {
+                    if self.is_finite() {
+                        Ok(self.to_string())
+                    } else {
+                        Err(Error::InvalidParameter(self.to_string()))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+to_sql_float!(f32, f64);
+
+impl<T: ToSql> ToSql for Option<T> {
+    fn to_sql(&self) -> Result<String, Error> {
+        match self {
+            Some(value) => value.to_sql(),
+            None => Ok("NULL".to_string()),
+        }
+    }
+}
+
+impl<T: ToSql + ?Sized> ToSql for &T {
+    fn to_sql(&self) -> Result<String, Error> {
+        (**self).to_sql()
+    }
+}
+
+impl<T: ToSql + ?Sized> ToSql for Box<T> {
+    fn to_sql(&self) -> Result<String, Error> {
+        (**self).to_sql()
+    }
+}
+
+/// Parameters of a query, either positional or named.
+///
+/// Implemented for `()`, for slices and arrays of `&dyn ToSql`, and for
+/// slices and arrays of `(name, &dyn ToSql)` pairs. Names may be given with
+/// or without the leading `:`. The [`params!`](crate::params!) and
+/// [`named_params!`](crate::named_params!) macros build them from values of
+/// mixed types.
+pub trait Params {
+    /// Values of `?` placeholders, in order.
+    fn positional(&self) -> &[&dyn ToSql] {
+        &[]
+    }
+
+    /// Values of `:name` placeholders, by name.
+    fn named(&self) -> &[(&str, &dyn ToSql)] {
+        &[]
+    }
+}
+
+impl Params for () {}
+
+impl Params for &[&dyn ToSql] {
+    fn positional(&self) -> &[&dyn ToSql] {
+        self
+    }
+}
+
+impl<const N: usize> Params for [&dyn ToSql; N] {
+    fn positional(&self) -> &[&dyn ToSql] {
+        self
+    }
+}
+
+impl<const N: usize> Params for &[&dyn ToSql; N] {
+    fn positional(&self) -> &[&dyn ToSql] {
+        *self
+    }
+}
+
+impl Params for &[(&str, &dyn ToSql)] {
+    fn named(&self) -> &[(&str, &dyn ToSql)] {
+        self
+    }
+}
+
+impl<const N: usize> Params for [(&str, &dyn ToSql); N] {
+    fn named(&self) -> &[(&str, &dyn ToSql)] {
+        self
+    }
+}
+
+impl<const N: usize> Params for &[(&str, &dyn ToSql); N] {
+    fn named(&self) -> &[(&str, &dyn ToSql)] {
+        *self
+    }
+}
+
+/// Positional parameters of mixed types.
+///
+/// ```rust
+/// use mdbsql::{params, Connection, Error};
+///
+/// # fn main() -> Result<(), Error> {
+/// let conn = Connection::open("./resource/test.mdb")?;
+/// let rows = conn.query("SELECT A FROM Table1 WHERE ID = ?", params![1])?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! params {
+    () => {
+        ()
+    };
+    ($($param:expr),+ $(,)?) => {
+        [$(&$param as &dyn $crate::ToSql),+]
+    };
+}
+
+/// Named parameters of mixed types.
+///
+/// ```rust
+/// use mdbsql::{named_params, Connection, Error};
+///
+/// # fn main() -> Result<(), Error> {
+/// let conn = Connection::open("./resource/test.mdb")?;
+/// let rows = conn.query(
+///     "SELECT A FROM Table1 WHERE ID = :id",
+///     named_params! { ":id": 1 },
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! named_params {
+    () => {
+        ()
+    };
+    ($($name:literal: $param:expr),+ $(,)?) => {
+        [$(($name, &$param as &dyn $crate::ToSql)),+]
+    };
+}
+
+/// Substitute the placeholders of `sql` with the rendered `params`.
+pub(crate) fn bind<P: Params>(sql: &str, params: &P) -> Result<String, Error> {
+    Template::parse(sql).render(params)
+}
+
+/// Query text split at its placeholders, parsed once to be rendered with
+/// different parameters.
+#[derive(Debug, Clone)]
+pub(crate) struct Template {
+    parts: Vec<Part>,
+    /// Number of `?` placeholders.
+    positional: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Positional,
+    Named(String),
+}
+
+impl Template {
+    pub fn parse(sql: &str) -> Self {
+        let mut parts = vec![];
+        let mut text = String::with_capacity(sql.len());
+        let mut chars = sql.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let placeholder = match c {
+                // literals and names are copied up to their closing character
+                '\'' | '"' | '[' => {
+                    let close = if c == '[' { ']' } else { c };
+                    let end = chars
+                        .by_ref()
+                        .find(|(_, c)| *c == close)
+                        .map_or(sql.len(), |(i, _)| i + 1);
+                    text.push_str(&sql[start..end]);
+                    continue;
+                }
+                '?' => Part::Positional,
+                ':' if chars
+                    .peek()
+                    .is_some_and(|(_, c)| c.is_ascii_alphabetic() || *c == '_') =>
+                {
+                    let mut end = sql.len();
+                    while let Some((i, c)) = chars.peek() {
+                        if !(c.is_ascii_alphanumeric() || *c == '_') {
+                            end = *i;
+                            break;
+                        }
+                        chars.next();
+                    }
+                    Part::Named(sql[start + 1..end].to_string())
+                }
+                c => {
+                    text.push(c);
+                    continue;
+                }
+            };
+            if !text.is_empty() {
+                parts.push(Part::Text(std::mem::take(&mut text)));
+            }
+            parts.push(placeholder);
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        let positional = parts
+            .iter()
+            .filter(|p| matches!(p, Part::Positional))
+            .count();
+        Self { parts, positional }
+    }
+
+    /// Query text with the placeholders replaced by `params`.
+    pub fn render<P: Params>(&self, params: &P) -> Result<String, Error> {
+        let positional = params.positional();
+        let named = params.named();
+        if self.positional != positional.len() {
+            return Err(Error::InvalidParameterCount(
+                self.positional,
+                positional.len(),
+            ));
+        }
+
+        let mut query = String::new();
+        let mut index = 0;
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => query.push_str(text),
+                Part::Positional => {
+                    query.push_str(&positional[index].to_sql()?);
+                    index += 1;
+                }
+                Part::Named(name) => {
+                    let (_, value) = named
+                        .iter()
+                        .find(|(n, _)| n.strip_prefix(':').unwrap_or(n) == name)
+                        .ok_or_else(|| Error::InvalidParameterName(name.to_string()))?;
+                    query.push_str(&value.to_sql()?);
+                }
+            }
+        }
+
+        Ok(query)
+    }
+
+    /// Query text with NULL for each placeholder, to be parsed without
+    /// parameters.
+    pub fn with_nulls(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.as_str(),
+                Part::Positional | Part::Named(_) => "NULL",
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn literals() {
+        assert_eq!("it's".to_sql().unwrap(), "'it''s'");
+        assert_eq!(true.to_sql().unwrap(), "1");
+        assert_eq!((-42i64).to_sql().unwrap(), "-42");
+        assert_eq!(1.5f64.to_sql().unwrap(), "1.5");
+        assert_eq!(1e20f64.to_sql().unwrap(), "100000000000000000000");
+        assert_eq!(None::<i32>.to_sql().unwrap(), "NULL");
+        assert!(matches!(f64::NAN.to_sql(), Err(Error::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn positional() {
+        let query = bind(
+            "SELECT * FROM [Who?] WHERE A = ? AND B = '?' AND C = ?",
+            &crate::params!["O'Brien", 2],
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            "SELECT * FROM [Who?] WHERE A = 'O''Brien' AND B = '?' AND C = 2"
+        );
+
+        assert!(matches!(
+            bind("SELECT * FROM T WHERE A = ?", &()),
+            Err(Error::InvalidParameterCount(1, 0))
+        ));
+        assert!(matches!(
+            bind("SELECT * FROM T", &crate::params![1]),
+            Err(Error::InvalidParameterCount(0, 1))
+        ));
+    }
+
+    #[test]
+    fn named() {
+        let query = bind(
+            "SELECT * FROM T WHERE A = :a AND B = ':a' AND C > :c_1 AND D < :a",
+            &crate::named_params! { ":a": "x", "c_1": 1.25 },
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            "SELECT * FROM T WHERE A = 'x' AND B = ':a' AND C > 1.25 AND D < 'x'"
+        );
+
+        assert!(matches!(
+            bind("SELECT * FROM T WHERE A = :b", &crate::named_params! { ":a": 1 }),
+            Err(Error::InvalidParameterName(name)) if name == "b"
+        ));
+    }
+
+    #[test]
+    fn with_nulls() {
+        let template = Template::parse("SELECT * FROM T WHERE A = ? AND B = ':a' AND C > :c");
+        assert_eq!(
+            template.with_nulls(),
+            "SELECT * FROM T WHERE A = NULL AND B = ':a' AND C > NULL"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::error::Error;
+use crate::params::ToSql;
+use crate::value::Value;
+
+/// Scale of the fixed-point MONEY type.
+const MONEY_SCALE: u32 = 4;
+
+impl TryFrom<&Value> for Decimal {
+    type Error = Error;
+
+    /// Converts MONEY and NUMERIC values exactly, and other numbers as is.
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Money(v) => Ok(Decimal::new(*v, MONEY_SCALE)),
+            Value::Numeric { value, scale, .. } => {
+                Decimal::try_from_i128_with_scale(*value, *scale as u32)
+                    .map_err(|e| Error::InvalidDecimal(e.to_string()))
+            }
+            Value::Byte(v) => Ok((*v).into()),
+            Value::Int(v) => Ok((*v).into()),
+            Value::LongInt(v) => Ok((*v).into()),
+            Value::Float(v) => {
+                Decimal::try_from(*v).map_err(|e| Error::InvalidDecimal(e.to_string()))
+            }
+            Value::Double(v) => {
+                Decimal::try_from(*v).map_err(|e| Error::InvalidDecimal(e.to_string()))
+            }
+            Value::Text(v) | Value::Memo(v) => {
+                Decimal::from_str(v).map_err(|e| Error::InvalidDecimal(e.to_string()))
+            }
+            v => Err(Error::InvalidDecimal(format!("{:?}", v))),
+        }
+    }
+}
+
+impl ToSql for Decimal {
+    fn to_sql(&self) -> Result<String, Error> {
+        Ok(self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Connection;
+
+    #[test]
+    fn money() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let values: Vec<Decimal> = conn
+            .prepare("select C from Table1")
+            .unwrap()
+            .map(|r| r.get(0).unwrap())
+            .collect();
+
+        assert_eq!(
+            values,
+            vec![Decimal::new(10000, 4), Decimal::new(990000, 4)]
+        );
+    }
+
+    #[test]
+    fn numeric_column() {
+        let conn = Connection::open("resource/numeric.mdb").unwrap();
+        let values: Vec<(Decimal, Option<Decimal>, Value)> = conn
+            .prepare("select C from Table1")
+            .unwrap()
+            .map(|r| Ok((r.get(0)?, r.get(0)?, r.get_value(0)?)))
+            .collect::<Result<_, Error>>()
+            .unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                (
+                    Decimal::new(125, 2),
+                    Some(Decimal::new(125, 2)),
+                    Value::Numeric {
+                        value: 125,
+                        precision: 18,
+                        scale: 2
+                    }
+                ),
+                (
+                    Decimal::new(-9950, 2),
+                    Some(Decimal::new(-9950, 2)),
+                    Value::Numeric {
+                        value: -9950,
+                        precision: 18,
+                        scale: 2
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn numeric() {
+        let value = Value::Numeric {
+            value: -123456,
+            precision: 18,
+            scale: 3,
+        };
+        assert_eq!(Decimal::try_from(&value).unwrap(), Decimal::new(-123456, 3));
+    }
+
+    #[test]
+    fn to_sql() {
+        assert_eq!(Decimal::new(-123456, 3).to_sql().unwrap(), "-123.456");
+    }
+}
@@ -0,0 +1,638 @@
+//! Saved queries, rebuilt as SQL from the rows of `MSysQueries`.
+//!
+//! Each query is stored as rows of attributes: its type, parameters, tables,
+//! columns, joins and clauses, in the order given by the `Order` column.
+
+use std::collections::BTreeMap;
+
+use crate::catalog::{CatalogEntry, ColumnType, ObjectType};
+use crate::error::Error;
+use crate::mdbsql::Rows;
+
+const ATTRIBUTE_TYPE: u8 = 1;
+const ATTRIBUTE_PARAMETER: u8 = 2;
+const ATTRIBUTE_FLAG: u8 = 3;
+const ATTRIBUTE_REMOTE_DB: u8 = 4;
+const ATTRIBUTE_TABLE: u8 = 5;
+const ATTRIBUTE_COLUMN: u8 = 6;
+const ATTRIBUTE_JOIN: u8 = 7;
+const ATTRIBUTE_WHERE: u8 = 8;
+const ATTRIBUTE_GROUP_BY: u8 = 9;
+const ATTRIBUTE_HAVING: u8 = 10;
+const ATTRIBUTE_ORDER_BY: u8 = 11;
+
+/// `Flag` bits of the flag attribute.
+const FLAG_SELECT_STAR: i32 = 0x01;
+const FLAG_DISTINCT: i32 = 0x02;
+const FLAG_OWNER_ACCESS: i32 = 0x04;
+const FLAG_DISTINCT_ROW: i32 = 0x08;
+const FLAG_TOP: i32 = 0x10;
+const FLAG_PERCENT: i32 = 0x20;
+/// Flag of union queries keeping duplicate rows.
+const FLAG_UNION_ALL: i32 = 0x02;
+
+/// `Flag` of crosstab columns.
+const CROSSTAB_VALUE: i32 = 1;
+const CROSSTAB_PIVOT: i32 = 2;
+
+/// Type of a saved query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryType {
+    /// SELECT.
+    Select,
+    /// SELECT ... INTO.
+    MakeTable,
+    /// INSERT INTO.
+    Append,
+    /// UPDATE.
+    Update,
+    /// DELETE.
+    Delete,
+    /// TRANSFORM ... PIVOT.
+    CrossTab,
+    /// CREATE, ALTER or DROP.
+    DataDefinition,
+    /// SQL sent as is to an ODBC source.
+    PassThrough,
+    /// UNION.
+    Union,
+    /// Type code without a known meaning.
+    Unknown(i32),
+}
+
+impl From<i32> for QueryType {
+    fn from(flag: i32) -> Self {
+        match flag {
+            1 => QueryType::Select,
+            2 => QueryType::MakeTable,
+            3 => QueryType::Append,
+            4 => QueryType::Update,
+            5 => QueryType::Delete,
+            6 => QueryType::CrossTab,
+            7 => QueryType::DataDefinition,
+            8 => QueryType::PassThrough,
+            9 => QueryType::Union,
+            flag => QueryType::Unknown(flag),
+        }
+    }
+}
+
+/// Parameter of a saved query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParameter {
+    /// Name of the parameter.
+    pub name: String,
+    /// Access type of the parameter.
+    pub parameter_type: ColumnType,
+}
+
+/// Query saved in a database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedQuery {
+    /// Name of the query.
+    pub name: String,
+    /// Type of the query.
+    pub query_type: QueryType,
+    /// Declared parameters.
+    pub parameters: Vec<QueryParameter>,
+    /// SQL text of the query, in Access syntax.
+    pub sql: String,
+}
+
+/// Row of `MSysQueries`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QueryRow {
+    pub attribute: u8,
+    pub expression: Option<String>,
+    pub flag: i32,
+    pub name1: Option<String>,
+    pub name2: Option<String>,
+    pub object_id: u32,
+    pub order: Vec<u8>,
+}
+
+/// Rebuild the queries of `catalog` from the rows of `MSysQueries`.
+pub(crate) fn saved_queries(
+    rows: Rows,
+    catalog: &[CatalogEntry],
+) -> Result<Vec<SavedQuery>, Error> {
+    let mut queries: BTreeMap<u32, Vec<QueryRow>> = BTreeMap::new();
+    for row in rows {
+        let query_row = QueryRow {
+            attribute: row.get("Attribute")?,
+            expression: row.get("Expression")?,
+            flag: row.get::<Option<i32>>("Flag")?.unwrap_or_default(),
+            name1: row.get("Name1")?,
+            name2: row.get("Name2")?,
+            object_id: row.get::<i64>("ObjectId")? as u32 & 0x00ff_ffff,
+            order: row.get::<Option<Vec<u8>>>("Order")?.unwrap_or_default(),
+        };
+        queries
+            .entry(query_row.object_id)
+            .or_default()
+            .push(query_row);
+    }
+
+    Ok(catalog
+        .iter()
+        .filter(|e| e.object_type == ObjectType::Query)
+        .filter_map(|e| {
+            let mut rows = queries.remove(&e.id)?;
+            rows.sort_by(|a, b| a.order.cmp(&b.order));
+            Some(build_query(&e.name, &rows))
+        })
+        .collect())
+}
+
+/// Rebuild a query from its attribute rows.
+pub(crate) fn build_query(name: &str, rows: &[QueryRow]) -> SavedQuery {
+    let query = Query { rows };
+    let query_type = query
+        .first(ATTRIBUTE_TYPE)
+        .map_or(QueryType::Select, |r| r.flag.into());
+
+    let parameters = query
+        .all(ATTRIBUTE_PARAMETER)
+        .map(|r| QueryParameter {
+            name: r.name1.clone().unwrap_or_default(),
+            parameter_type: ColumnType::from(r.flag),
+        })
+        .collect();
+
+    let body = match query_type {
+        QueryType::Select => query.select(None),
+        QueryType::MakeTable => query.select(query.target()),
+        QueryType::Append => query.append(),
+        QueryType::Update => query.update(),
+        QueryType::Delete => query.delete(),
+        QueryType::CrossTab => query.crosstab(),
+        QueryType::Union => query.union(),
+        QueryType::DataDefinition | QueryType::PassThrough | QueryType::Unknown(_) => query
+            .first(ATTRIBUTE_TYPE)
+            .and_then(|r| r.expression.clone())
+            .unwrap_or_default(),
+    };
+
+    SavedQuery {
+        name: name.to_string(),
+        query_type,
+        sql: format!("{}{};", query.parameters(), body),
+        parameters,
+    }
+}
+
+struct Query<'a> {
+    rows: &'a [QueryRow],
+}
+
+impl<'a> Query<'a> {
+    fn all(&self, attribute: u8) -> impl Iterator<Item = &'a QueryRow> {
+        self.rows.iter().filter(move |r| r.attribute == attribute)
+    }
+
+    fn first(&self, attribute: u8) -> Option<&'a QueryRow> {
+        self.all(attribute).next()
+    }
+
+    fn flags(&self) -> i32 {
+        self.first(ATTRIBUTE_FLAG).map_or(0, |r| r.flag)
+    }
+
+    fn expressions(&self, attribute: u8) -> Vec<String> {
+        self.all(attribute)
+            .filter_map(|r| r.expression.clone())
+            .collect()
+    }
+
+    /// Target table of make-table and append queries.
+    fn target(&self) -> Option<String> {
+        self.first(ATTRIBUTE_TYPE)
+            .and_then(|r| r.name1.as_deref())
+            .map(quote)
+    }
+
+    fn parameters(&self) -> String {
+        let parameters: Vec<String> = self
+            .all(ATTRIBUTE_PARAMETER)
+            .map(|r| {
+                format!(
+                    "{} {}",
+                    quote(r.name1.as_deref().unwrap_or_default()),
+                    type_name(ColumnType::from(r.flag))
+                )
+            })
+            .collect();
+        match parameters.is_empty() {
+            true => String::new(),
+            false => format!("PARAMETERS {};\n", parameters.join(", ")),
+        }
+    }
+
+    fn columns(&self, columns: impl Iterator<Item = &'a QueryRow>) -> Vec<String> {
+        columns
+            .map(|r| {
+                let expression = r.expression.clone().unwrap_or_default();
+                match &r.name1 {
+                    Some(alias) => format!("{} AS {}", expression, quote(alias)),
+                    None => expression,
+                }
+            })
+            .collect()
+    }
+
+    /// SELECT clause, with `DISTINCT`, `TOP` and `*`.
+    fn select_clause(&self, columns: Vec<String>) -> String {
+        let flags = self.flags();
+        let mut sql = "SELECT ".to_string();
+        if flags & FLAG_DISTINCT != 0 {
+            sql.push_str("DISTINCT ");
+        } else if flags & FLAG_DISTINCT_ROW != 0 {
+            sql.push_str("DISTINCTROW ");
+        }
+        if flags & FLAG_TOP != 0 {
+            let top = self.first(ATTRIBUTE_FLAG).and_then(|r| r.name1.clone());
+            sql.push_str(&format!("TOP {} ", top.unwrap_or_default()));
+            if flags & FLAG_PERCENT != 0 {
+                sql.push_str("PERCENT ");
+            }
+        }
+
+        let mut columns = columns;
+        if flags & FLAG_SELECT_STAR != 0 {
+            columns.insert(0, "*".to_string());
+        }
+        sql.push_str(&columns.join(", "));
+        sql
+    }
+
+    /// FROM clause, nesting joined tables like Access does.
+    fn source_clause(&self) -> String {
+        // tables with the names they are referred to by
+        let mut tables: Vec<(Vec<String>, String)> = self
+            .all(ATTRIBUTE_TABLE)
+            .map(|r| {
+                let name = r.name1.clone().unwrap_or_default();
+                let source = match &r.expression {
+                    Some(subquery) => format!("({})", subquery),
+                    None => quote(&name),
+                };
+                match &r.name2 {
+                    Some(alias) => (
+                        vec![alias.clone()],
+                        format!("{} AS {}", source, quote(alias)),
+                    ),
+                    None => (vec![name], source),
+                }
+            })
+            .collect();
+
+        for join in self.all(ATTRIBUTE_JOIN) {
+            let find = |name: &Option<String>, tables: &[(Vec<String>, String)]| {
+                tables.iter().position(|(names, _)| {
+                    name.as_ref()
+                        .is_some_and(|n| names.iter().any(|t| t.eq_ignore_ascii_case(n)))
+                })
+            };
+            let (left, right) = match (find(&join.name1, &tables), find(&join.name2, &tables)) {
+                (Some(left), Some(right)) if left != right => (left, right),
+                _ => continue,
+            };
+
+            let kind = match join.flag {
+                2 => "LEFT JOIN",
+                3 => "RIGHT JOIN",
+                _ => "INNER JOIN",
+            };
+            let nest = |(names, sql): &(Vec<String>, String)| match names.len() {
+                1 => sql.clone(),
+                _ => format!("({})", sql),
+            };
+            let sql = format!(
+                "{} {} {} ON {}",
+                nest(&tables[left]),
+                kind,
+                nest(&tables[right]),
+                join.expression.as_deref().unwrap_or_default()
+            );
+
+            let (right_names, _) = tables.remove(right);
+            let left = if right < left { left - 1 } else { left };
+            tables[left].0.extend(right_names);
+            tables[left].1 = sql;
+        }
+
+        let mut sql = format!(
+            "FROM {}",
+            tables
+                .into_iter()
+                .map(|(_, sql)| sql)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if let Some(remote) = self.first(ATTRIBUTE_REMOTE_DB) {
+            let db = remote.name1.as_deref().unwrap_or_default();
+            sql.push_str(&format!(" IN '{}'", db));
+            if let Some(connect) = &remote.expression {
+                sql.push_str(&format!(" [{}]", connect));
+            }
+        }
+        sql
+    }
+
+    /// WHERE, GROUP BY, HAVING and ORDER BY clauses.
+    fn clauses(&self) -> String {
+        let mut sql = String::new();
+        if let Some(condition) = self
+            .first(ATTRIBUTE_WHERE)
+            .and_then(|r| r.expression.as_ref())
+        {
+            sql.push_str(&format!("\nWHERE {}", condition));
+        }
+        let group_by = self.expressions(ATTRIBUTE_GROUP_BY);
+        if !group_by.is_empty() {
+            sql.push_str(&format!("\nGROUP BY {}", group_by.join(", ")));
+        }
+        if let Some(condition) = self
+            .first(ATTRIBUTE_HAVING)
+            .and_then(|r| r.expression.as_ref())
+        {
+            sql.push_str(&format!("\nHAVING {}", condition));
+        }
+        sql.push_str(&self.order_by());
+        if self.flags() & FLAG_OWNER_ACCESS != 0 {
+            sql.push_str("\nWITH OWNERACCESS OPTION");
+        }
+        sql
+    }
+
+    /// ORDER BY clause, with descending expressions marked `D`.
+    fn order_by(&self) -> String {
+        let order_by: Vec<String> = self
+            .all(ATTRIBUTE_ORDER_BY)
+            .map(|r| {
+                let expression = r.expression.clone().unwrap_or_default();
+                match r.name1.as_deref() {
+                    Some("D") => format!("{} DESC", expression),
+                    _ => expression,
+                }
+            })
+            .collect();
+        match order_by.is_empty() {
+            true => String::new(),
+            false => format!("\nORDER BY {}", order_by.join(", ")),
+        }
+    }
+
+    fn select(&self, into: Option<String>) -> String {
+        let mut sql = self.select_clause(self.columns(self.all(ATTRIBUTE_COLUMN)));
+        if let Some(into) = into {
+            sql.push_str(&format!(" INTO {}", into));
+        }
+        format!("{}\n{}{}", sql, self.source_clause(), self.clauses())
+    }
+
+    fn append(&self) -> String {
+        let targets: Vec<String> = self
+            .all(ATTRIBUTE_COLUMN)
+            .filter_map(|r| r.name2.as_deref().map(quote))
+            .collect();
+        let mut sql = format!("INSERT INTO {}", self.target().unwrap_or_default());
+        if !targets.is_empty() {
+            sql.push_str(&format!(" ({})", targets.join(", ")));
+        }
+
+        let values: Vec<String> = self
+            .all(ATTRIBUTE_COLUMN)
+            .map(|r| r.expression.clone().unwrap_or_default())
+            .collect();
+        if self.first(ATTRIBUTE_TABLE).is_some() {
+            format!(
+                "{}\n{}\n{}{}",
+                sql,
+                self.select_clause(values),
+                self.source_clause(),
+                self.clauses()
+            )
+        } else {
+            format!("{}\nVALUES ({})", sql, values.join(", "))
+        }
+    }
+
+    fn update(&self) -> String {
+        let from = self.source_clause();
+        let values: Vec<String> = self
+            .all(ATTRIBUTE_COLUMN)
+            .map(|r| {
+                format!(
+                    "{} = {}",
+                    quote(r.name2.as_deref().unwrap_or_default()),
+                    r.expression.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        format!(
+            "UPDATE {}\nSET {}{}",
+            from.trim_start_matches("FROM "),
+            values.join(", "),
+            self.clauses()
+        )
+    }
+
+    fn delete(&self) -> String {
+        let columns = self.columns(self.all(ATTRIBUTE_COLUMN));
+        let mut sql = "DELETE".to_string();
+        if self.flags() & FLAG_SELECT_STAR != 0 {
+            sql.push_str(" *");
+        }
+        if !columns.is_empty() {
+            sql.push_str(&format!(" {}", columns.join(", ")));
+        }
+        format!("{}\n{}{}", sql, self.source_clause(), self.clauses())
+    }
+
+    fn crosstab(&self) -> String {
+        let columns =
+            |flag| self.columns(self.all(ATTRIBUTE_COLUMN).filter(move |r| r.flag == flag));
+        let select = self.columns(
+            self.all(ATTRIBUTE_COLUMN)
+                .filter(|r| r.flag != CROSSTAB_VALUE && r.flag != CROSSTAB_PIVOT),
+        );
+        format!(
+            "TRANSFORM {}\n{}\n{}{}\nPIVOT {}",
+            columns(CROSSTAB_VALUE).join(", "),
+            self.select_clause(select),
+            self.source_clause(),
+            self.clauses(),
+            columns(CROSSTAB_PIVOT).join(", ")
+        )
+    }
+
+    fn union(&self) -> String {
+        let union = match self.flags() & FLAG_UNION_ALL {
+            0 => "\nUNION\n",
+            _ => "\nUNION ALL\n",
+        };
+        self.expressions(ATTRIBUTE_TABLE).join(union) + &self.order_by()
+    }
+}
+
+/// Quote a name with brackets unless it is a plain identifier.
+fn quote(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match plain {
+        true => name.to_string(),
+        false => format!("[{}]", name),
+    }
+}
+
+/// Access SQL name of a parameter type.
+fn type_name(parameter_type: ColumnType) -> &'static str {
+    match parameter_type {
+        ColumnType::Bool => "Bit",
+        ColumnType::Byte => "Byte",
+        ColumnType::Int => "Short",
+        ColumnType::LongInt => "Long",
+        ColumnType::Money => "Currency",
+        ColumnType::Float => "IEEESingle",
+        ColumnType::Double => "IEEEDouble",
+        ColumnType::DateTime => "DateTime",
+        ColumnType::Binary => "Binary",
+        ColumnType::Text => "Text",
+        ColumnType::Ole => "LongBinary",
+        ColumnType::Memo => "LongText",
+        ColumnType::RepId => "Guid",
+        ColumnType::Numeric => "Decimal",
+        ColumnType::Complex | ColumnType::Unknown(_) => "Value",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn row(attribute: u8, expression: Option<&str>, flag: i32, name1: Option<&str>) -> QueryRow {
+        QueryRow {
+            attribute,
+            expression: expression.map(|s| s.to_string()),
+            flag,
+            name1: name1.map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn with_name2(mut row: QueryRow, name2: &str) -> QueryRow {
+        row.name2 = Some(name2.to_string());
+        row
+    }
+
+    #[test]
+    fn select() {
+        let rows = vec![
+            row(ATTRIBUTE_TYPE, None, 1, None),
+            row(ATTRIBUTE_PARAMETER, None, 4, Some("Min B")),
+            row(ATTRIBUTE_FLAG, None, FLAG_DISTINCT | FLAG_TOP, Some("10")),
+            row(ATTRIBUTE_TABLE, None, 0, Some("Table1")),
+            with_name2(row(ATTRIBUTE_TABLE, None, 0, Some("Order Lines")), "L"),
+            row(ATTRIBUTE_COLUMN, Some("Table1.A"), 0, None),
+            row(ATTRIBUTE_COLUMN, Some("Sum(L.Qty)"), 0, Some("Total")),
+            with_name2(
+                row(ATTRIBUTE_JOIN, Some("Table1.ID = L.ID"), 2, Some("Table1")),
+                "L",
+            ),
+            row(ATTRIBUTE_WHERE, Some("Table1.B >= [Min B]"), 0, None),
+            row(ATTRIBUTE_GROUP_BY, Some("Table1.A"), 0, None),
+            row(ATTRIBUTE_ORDER_BY, Some("Table1.A"), 0, Some("D")),
+        ];
+
+        let query = build_query("Totals", &rows);
+        assert_eq!(query.query_type, QueryType::Select);
+        assert_eq!(
+            query.parameters,
+            vec![QueryParameter {
+                name: "Min B".to_string(),
+                parameter_type: ColumnType::LongInt
+            }]
+        );
+        assert_eq!(
+            query.sql,
+            "PARAMETERS [Min B] Long;\n\
+             SELECT DISTINCT TOP 10 Table1.A, Sum(L.Qty) AS Total\n\
+             FROM Table1 LEFT JOIN [Order Lines] AS L ON Table1.ID = L.ID\n\
+             WHERE Table1.B >= [Min B]\n\
+             GROUP BY Table1.A\n\
+             ORDER BY Table1.A DESC;"
+        );
+    }
+
+    #[test]
+    fn nested_joins() {
+        let rows = vec![
+            row(ATTRIBUTE_FLAG, None, FLAG_SELECT_STAR, None),
+            row(ATTRIBUTE_TABLE, None, 0, Some("A")),
+            row(ATTRIBUTE_TABLE, None, 0, Some("B")),
+            row(ATTRIBUTE_TABLE, None, 0, Some("C")),
+            with_name2(row(ATTRIBUTE_JOIN, Some("A.x = B.x"), 1, Some("A")), "B"),
+            with_name2(row(ATTRIBUTE_JOIN, Some("B.y = C.y"), 1, Some("B")), "C"),
+        ];
+        assert_eq!(
+            build_query("Joins", &rows).sql,
+            "SELECT *\nFROM (A INNER JOIN B ON A.x = B.x) INNER JOIN C ON B.y = C.y;"
+        );
+    }
+
+    #[test]
+    fn action_queries() {
+        let rows = vec![
+            row(ATTRIBUTE_TYPE, None, 4, None),
+            row(ATTRIBUTE_TABLE, None, 0, Some("Table1")),
+            with_name2(row(ATTRIBUTE_COLUMN, Some("B + 1"), 0, None), "B"),
+            row(ATTRIBUTE_WHERE, Some("E = True"), 0, None),
+        ];
+        let query = build_query("Increment", &rows);
+        assert_eq!(query.query_type, QueryType::Update);
+        assert_eq!(query.sql, "UPDATE Table1\nSET B = B + 1\nWHERE E = True;");
+
+        let rows = vec![
+            row(ATTRIBUTE_TYPE, None, 3, Some("Archive")),
+            row(ATTRIBUTE_TABLE, None, 0, Some("Table1")),
+            with_name2(row(ATTRIBUTE_COLUMN, Some("Table1.A"), 0, None), "A"),
+        ];
+        assert_eq!(
+            build_query("Append", &rows).sql,
+            "INSERT INTO Archive (A)\nSELECT Table1.A\nFROM Table1;"
+        );
+
+        let rows = vec![row(ATTRIBUTE_TYPE, Some("DROP TABLE Archive"), 7, None)];
+        assert_eq!(build_query("Drop", &rows).sql, "DROP TABLE Archive;");
+    }
+
+    #[test]
+    fn union() {
+        let rows = vec![
+            row(ATTRIBUTE_TYPE, None, 9, None),
+            row(ATTRIBUTE_FLAG, None, FLAG_UNION_ALL, None),
+            row(ATTRIBUTE_TABLE, Some("SELECT A FROM Table1"), 0, None),
+            row(ATTRIBUTE_TABLE, Some("SELECT A FROM Table2"), 0, None),
+        ];
+        assert_eq!(
+            build_query("Union", &rows).sql,
+            "SELECT A FROM Table1\nUNION ALL\nSELECT A FROM Table2;"
+        );
+
+        let rows = vec![
+            row(ATTRIBUTE_TYPE, None, 9, None),
+            row(ATTRIBUTE_TABLE, Some("SELECT A, B FROM Table1"), 0, None),
+            row(ATTRIBUTE_TABLE, Some("SELECT A, B FROM Table2"), 0, None),
+            row(ATTRIBUTE_ORDER_BY, Some("A"), 0, Some("D")),
+            row(ATTRIBUTE_ORDER_BY, Some("B"), 0, None),
+        ];
+        assert_eq!(
+            build_query("Union", &rows).sql,
+            "SELECT A, B FROM Table1\nUNION\nSELECT A, B FROM Table2\nORDER BY A DESC, B;"
+        );
+    }
+}
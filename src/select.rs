@@ -0,0 +1,1839 @@
+//! Evaluation of queries beyond the SQL subset of libmdbsql.
+//!
+//! A query is parsed in full, and only its projection, WHERE clause and LIMIT
+//! are run by libmdbsql. ORDER BY, GROUP BY, HAVING, aggregates and DISTINCT
+//! are then evaluated over the typed rows. Text is compared ignoring case, as
+//! Access does.
+//!
+//! Queries with INNER or LEFT JOIN read each table with libmdbsql, and join
+//! its rows to the rows of the tables before it with a hash join on the
+//! equalities of the ON clause. Their WHERE clause is evaluated over the
+//! joined rows.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use crate::catalog::SortOrder;
+use crate::error::Error;
+use crate::value::Value;
+
+/// Rows of a query evaluated by [`Connection::select`](crate::Connection::select).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResultSet {
+    /// Names of the columns.
+    pub columns: Vec<String>,
+    /// Values of each row, in column order.
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl ResultSet {
+    /// Position of a column by name, ignoring case.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Words ending an expression or a clause, which cannot be implicit aliases.
+const RESERVED: &[&str] = &[
+    "AND", "AS", "ASC", "BY", "DESC", "DISTINCT", "FROM", "GROUP", "HAVING", "INNER", "IS", "JOIN",
+    "LEFT", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "OUTER", "RIGHT", "SELECT", "TOP",
+    "WHERE",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Bare word, either a keyword or a name.
+    Word(String),
+    /// Name in brackets.
+    Name(String),
+    Number(String),
+    Str(String),
+    Symbol(&'static str),
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::InvalidQuery(msg.into())
+}
+
+/// Quote a table or column name with brackets, doubling the `]` in it.
+pub(crate) fn bracket(name: &str) -> String {
+    format!("[{}]", name.replace(']', "]]"))
+}
+
+fn tokenize(sql: &str) -> Result<Vec<(Token, Range<usize>)>, Error> {
+    const SYMBOLS: &[&str] = &[
+        "<>", "<=", ">=", "!=", "=", "<", ">", "(", ")", ",", "*", ".", "-", ";",
+    ];
+
+    let mut tokens = vec![];
+    let mut chars = sql.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let token = if c.is_whitespace() {
+            chars.next();
+            continue;
+        } else if c.is_alphabetic() || c == '_' {
+            while chars
+                .next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+                .is_some()
+            {}
+            let end = chars.peek().map_or(sql.len(), |(i, _)| *i);
+            Token::Word(sql[start..end].to_string())
+        } else if c.is_ascii_digit() {
+            while chars
+                .next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                .is_some()
+            {}
+            if chars.next_if(|(_, c)| *c == 'e' || *c == 'E').is_some() {
+                chars.next_if(|(_, c)| *c == '-' || *c == '+');
+                while chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+            }
+            let end = chars.peek().map_or(sql.len(), |(i, _)| *i);
+            Token::Number(sql[start..end].to_string())
+        } else if c == '[' {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    // closing brackets are escaped by doubling them
+                    Some((_, ']')) => match chars.next_if(|(_, c)| *c == ']') {
+                        Some(_) => name.push(']'),
+                        None => break,
+                    },
+                    Some((_, v)) => name.push(v),
+                    None => return Err(invalid("unclosed bracket")),
+                }
+            }
+            Token::Name(name)
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    // quotes are escaped by doubling them
+                    Some((_, q)) if q == c => match chars.next_if(|(_, q)| *q == c) {
+                        Some(_) => value.push(c),
+                        None => break,
+                    },
+                    Some((_, v)) => value.push(v),
+                    None => return Err(invalid("unclosed string")),
+                }
+            }
+            Token::Str(value)
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| sql[start..].starts_with(**s))
+                .ok_or_else(|| invalid(format!("unexpected character '{}'", c)))?;
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            Token::Symbol(symbol)
+        };
+        let end = chars.peek().map_or(sql.len(), |(i, _)| *i);
+        tokens.push((token, start..end));
+    }
+    Ok(tokens)
+}
+
+/// Aggregate function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            ("COUNT", Aggregate::Count),
+            ("SUM", Aggregate::Sum),
+            ("AVG", Aggregate::Avg),
+            ("MIN", Aggregate::Min),
+            ("MAX", Aggregate::Max),
+        ]
+        .into_iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, f)| f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Some(match symbol {
+            "=" => CompareOp::Eq,
+            "<>" | "!=" => CompareOp::Ne,
+            "<" => CompareOp::Lt,
+            "<=" => CompareOp::Le,
+            ">" => CompareOp::Gt,
+            ">=" => CompareOp::Ge,
+            _ => return None,
+        })
+    }
+
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Column {
+        table: Option<String>,
+        name: String,
+    },
+    Literal(Value),
+    /// Aggregate over an expression, or over rows for `COUNT(*)`.
+    Aggregate(Aggregate, Option<Box<Expr>>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    IsNull(Box<Expr>, bool),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    /// Call `f` with each expression of the tree, outside aggregates if
+    /// `aggregates` is unset.
+    fn walk<'a>(&'a self, aggregates: bool, f: &mut impl FnMut(&'a Expr)) {
+        f(self);
+        match self {
+            Expr::Column { .. } | Expr::Literal(_) => {}
+            Expr::Aggregate(_, arg) => {
+                if let Some(arg) = arg.as_deref().filter(|_| aggregates) {
+                    arg.walk(aggregates, f);
+                }
+            }
+            Expr::IsNull(e, _) | Expr::Not(e) => e.walk(aggregates, f),
+            Expr::Compare(_, l, r) | Expr::And(l, r) | Expr::Or(l, r) => {
+                l.walk(aggregates, f);
+                r.walk(aggregates, f);
+            }
+        }
+    }
+
+    fn has_aggregate(&self) -> bool {
+        let mut found = false;
+        self.walk(false, &mut |e| found |= matches!(e, Expr::Aggregate(..)));
+        found
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SelectItem {
+    /// `*`, or `table.*`.
+    Wildcard(Option<String>),
+    Expr {
+        expr: Expr,
+        name: String,
+    },
+}
+
+/// Table of the FROM clause.
+#[derive(Debug, Clone, PartialEq)]
+struct TableRef {
+    name: String,
+    alias: Option<String>,
+    /// Text of the table name in the query.
+    span: Range<usize>,
+}
+
+impl TableRef {
+    fn is_named(&self, name: &str) -> bool {
+        self.alias
+            .as_deref()
+            .unwrap_or(&self.name)
+            .eq_ignore_ascii_case(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JoinKind {
+    Inner,
+    /// Rows of the left side without a match are kept, padded with NULL.
+    Left,
+}
+
+/// Table joined to the tables before it.
+#[derive(Debug, Clone, PartialEq)]
+struct Join {
+    kind: JoinKind,
+    table: TableRef,
+    on: Expr,
+}
+
+/// Keys of a join, as positions in the rows joined so far and in the rows of
+/// the joined table.
+type JoinKeys = Vec<(usize, usize)>;
+
+/// WHERE clause.
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    /// Text passed to libmdbsql as is, for queries of one table.
+    Pushed(Range<usize>),
+    /// Condition evaluated over joined rows.
+    Evaluated(Expr),
+}
+
+/// Term of ORDER BY.
+#[derive(Debug, Clone, PartialEq)]
+enum OrderKey {
+    /// Column of the result, by alias or position.
+    Output(usize),
+    Expr(Expr),
+}
+
+/// Parsed SELECT query.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Select {
+    sql: String,
+    distinct: bool,
+    top: Option<usize>,
+    items: Vec<SelectItem>,
+    from: TableRef,
+    joins: Vec<Join>,
+    filter: Option<Filter>,
+    group_by: Vec<Expr>,
+    having: Option<Expr>,
+    order_by: Vec<(OrderKey, SortOrder)>,
+    limit: Option<usize>,
+}
+
+struct Parser<'a> {
+    sql: &'a str,
+    tokens: Vec<(Token, Range<usize>)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    /// Offset of the next token in the query.
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.sql.len(), |(_, span)| span.start)
+    }
+
+    /// Offset of the end of the last token.
+    fn end(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map_or(0, |(_, span)| span.end)
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| invalid("unexpected end of query"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        self.pos += found as usize;
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(keyword))
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        self.pos += found as usize;
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), Error> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(symbol))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        match self.tokens.get(self.pos) {
+            Some((_, span)) => invalid(format!(
+                "expected {} at '{}'",
+                expected,
+                &self.sql[span.start..]
+            )),
+            None => invalid(format!("expected {} at end of query", expected)),
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, Error> {
+        match self.next()? {
+            Token::Number(n) => n
+                .parse()
+                .map_err(|_| invalid(format!("invalid count: {}", n))),
+            _ => {
+                self.pos -= 1;
+                Err(self.unexpected("a number"))
+            }
+        }
+    }
+
+    /// Name of a column, table or alias.
+    fn name(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            Some(Token::Word(word)) if !is_reserved(word) => {
+                let word = word.clone();
+                self.pos += 1;
+                Ok(word)
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    /// Alias after `AS`, or a name which is not a keyword.
+    fn alias(&mut self) -> Result<Option<String>, Error> {
+        if self.eat_keyword("AS") {
+            return self.name().map(Some);
+        }
+        match self.peek() {
+            Some(Token::Name(_)) => self.name().map(Some),
+            Some(Token::Word(word)) if !is_reserved(word) => self.name().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn select(&mut self) -> Result<Select, Error> {
+        self.expect_keyword("SELECT")?;
+        let distinct = self.eat_keyword("DISTINCT");
+        let top = match self.eat_keyword("TOP") {
+            true => Some(self.number()?),
+            false => None,
+        };
+
+        let mut items = vec![self.select_item()?];
+        while self.eat_symbol(",") {
+            items.push(self.select_item()?);
+        }
+
+        self.expect_keyword("FROM")?;
+        // Access nests joins in parentheses: (a JOIN b ON ...) JOIN c ON ...
+        let mut depth = 0;
+        while self.eat_symbol("(") {
+            depth += 1;
+        }
+        let from = self.table_ref()?;
+        let mut joins: Vec<Join> = vec![];
+        while let Some(kind) = self.join_kind()? {
+            let table = self.table_ref()?;
+            let name = table.alias.as_deref().unwrap_or(&table.name);
+            if from.is_named(name) || joins.iter().any(|j| j.table.is_named(name)) {
+                return Err(invalid(format!("duplicate table name: {}", name)));
+            }
+            self.expect_keyword("ON")?;
+            let on = self.expr()?;
+            if on.has_aggregate() {
+                return Err(invalid("aggregate in ON"));
+            }
+            joins.push(Join { kind, table, on });
+            if depth > 0 && self.eat_symbol(")") {
+                depth -= 1;
+            }
+        }
+        if depth > 0 {
+            return Err(self.unexpected("JOIN"));
+        }
+
+        let filter = match self.eat_keyword("WHERE") {
+            true if joins.is_empty() => Some(Filter::Pushed(self.filter()?)),
+            true => {
+                let expr = self.expr()?;
+                if expr.has_aggregate() {
+                    return Err(invalid("aggregate in WHERE"));
+                }
+                Some(Filter::Evaluated(expr))
+            }
+            false => None,
+        };
+
+        let mut group_by = vec![];
+        if self.eat_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            group_by.push(self.expr()?);
+            while self.eat_symbol(",") {
+                group_by.push(self.expr()?);
+            }
+        }
+
+        let having = match self.eat_keyword("HAVING") {
+            true => Some(self.expr()?),
+            false => None,
+        };
+
+        let mut order_by = vec![];
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            loop {
+                let expr = self.expr()?;
+                let order = if self.eat_keyword("DESC") {
+                    SortOrder::Descending
+                } else {
+                    self.eat_keyword("ASC");
+                    SortOrder::Ascending
+                };
+                order_by.push((output_key(expr, &items)?, order));
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let limit = match self.eat_keyword("LIMIT") {
+            true => Some(self.number()?),
+            false => None,
+        };
+
+        self.eat_symbol(";");
+        if self.pos < self.tokens.len() {
+            return Err(self.unexpected("end of query"));
+        }
+
+        Ok(Select {
+            sql: self.sql.to_string(),
+            distinct,
+            top,
+            items,
+            from,
+            joins,
+            filter,
+            group_by,
+            having,
+            order_by,
+            limit,
+        })
+    }
+
+    fn select_item(&mut self) -> Result<SelectItem, Error> {
+        if self.eat_symbol("*") {
+            return Ok(SelectItem::Wildcard(None));
+        }
+        // table.*
+        if matches!(
+            self.tokens.get(self.pos + 1..self.pos + 3),
+            Some([(Token::Symbol("."), _), (Token::Symbol("*"), _)])
+        ) {
+            let table = self.name()?;
+            self.pos += 2;
+            return Ok(SelectItem::Wildcard(Some(table)));
+        }
+
+        let start = self.offset();
+        let expr = self.expr()?;
+        let end = self.end();
+        let name = match self.alias()? {
+            Some(alias) => alias,
+            None => match &expr {
+                Expr::Column { name, .. } => name.clone(),
+                _ => self.sql[start..end].to_string(),
+            },
+        };
+        Ok(SelectItem::Expr { expr, name })
+    }
+
+    fn table_ref(&mut self) -> Result<TableRef, Error> {
+        let start = self.offset();
+        let name = self.name()?;
+        let span = start..self.end();
+        let alias = self.alias()?;
+        Ok(TableRef { name, alias, span })
+    }
+
+    fn join_kind(&mut self) -> Result<Option<JoinKind>, Error> {
+        if self.eat_keyword("JOIN") {
+            return Ok(Some(JoinKind::Inner));
+        }
+        if self.eat_keyword("INNER") {
+            self.expect_keyword("JOIN")?;
+            return Ok(Some(JoinKind::Inner));
+        }
+        if self.eat_keyword("LEFT") {
+            self.eat_keyword("OUTER");
+            self.expect_keyword("JOIN")?;
+            return Ok(Some(JoinKind::Left));
+        }
+        if self.is_keyword("RIGHT") {
+            return Err(invalid(
+                "RIGHT JOIN is not supported, swap the tables of a LEFT JOIN",
+            ));
+        }
+        Ok(None)
+    }
+
+    /// Skip the WHERE clause up to the next clause.
+    fn filter(&mut self) -> Result<Range<usize>, Error> {
+        let start = self.offset();
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Symbol("(") => depth += 1,
+                Token::Symbol(")") => depth = depth.saturating_sub(1),
+                Token::Symbol(";") if depth == 0 => break,
+                Token::Word(w)
+                    if depth == 0
+                        && ["GROUP", "HAVING", "ORDER", "LIMIT"]
+                            .iter()
+                            .any(|k| w.eq_ignore_ascii_case(k)) =>
+                {
+                    break
+                }
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        if self.offset() == start {
+            return Err(self.unexpected("a condition"));
+        }
+        Ok(start..self.end())
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and_expr()?;
+        while self.eat_keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.not_expr()?;
+        while self.eat_keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, Error> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.not_expr()?)));
+        }
+        let expr = self.primary()?;
+
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull(Box::new(expr), negated));
+        }
+        if let Some(Token::Symbol(symbol)) = self.peek() {
+            if let Some(op) = CompareOp::from_symbol(symbol) {
+                self.pos += 1;
+                return Ok(Expr::Compare(op, Box::new(expr), Box::new(self.primary()?)));
+            }
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        match self.next()? {
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Token::Symbol("-") => match self.next()? {
+                Token::Number(n) => number(&format!("-{}", n)),
+                _ => {
+                    self.pos -= 1;
+                    Err(self.unexpected("a number"))
+                }
+            },
+            Token::Number(n) => number(&n),
+            Token::Str(s) => Ok(Expr::Literal(Value::Text(s))),
+            Token::Word(w) if w.eq_ignore_ascii_case("NULL") => Ok(Expr::Literal(Value::Null)),
+            Token::Word(w) if w.eq_ignore_ascii_case("TRUE") => {
+                Ok(Expr::Literal(Value::Bool(true)))
+            }
+            Token::Word(w) if w.eq_ignore_ascii_case("FALSE") => {
+                Ok(Expr::Literal(Value::Bool(false)))
+            }
+            Token::Word(w) if self.eat_symbol("(") => {
+                let function = Aggregate::from_name(&w)
+                    .ok_or_else(|| invalid(format!("unsupported function: {}", w)))?;
+                let arg = if function == Aggregate::Count && self.eat_symbol("*") {
+                    None
+                } else {
+                    let arg = self.expr()?;
+                    if arg.has_aggregate() {
+                        return Err(invalid(format!("nested aggregate in {}", w)));
+                    }
+                    Some(Box::new(arg))
+                };
+                self.expect_symbol(")")?;
+                Ok(Expr::Aggregate(function, arg))
+            }
+            _ => {
+                self.pos -= 1;
+                let name = self.name()?;
+                if self.eat_symbol(".") {
+                    Ok(Expr::Column {
+                        table: Some(name),
+                        name: self.name()?,
+                    })
+                } else {
+                    Ok(Expr::Column { table: None, name })
+                }
+            }
+        }
+    }
+}
+
+fn is_reserved(word: &str) -> bool {
+    RESERVED.iter().any(|r| r.eq_ignore_ascii_case(word))
+}
+
+fn number(text: &str) -> Result<Expr, Error> {
+    let value = match text.parse::<i32>() {
+        Ok(n) => Value::LongInt(n),
+        Err(_) => Value::Double(
+            text.parse()
+                .map_err(|_| invalid(format!("invalid number: {}", text)))?,
+        ),
+    };
+    Ok(Expr::Literal(value))
+}
+
+/// ORDER BY term, naming a column of the result by alias or position.
+fn output_key(expr: Expr, items: &[SelectItem]) -> Result<OrderKey, Error> {
+    match &expr {
+        Expr::Literal(Value::LongInt(n)) => {
+            if items.iter().any(|i| matches!(i, SelectItem::Wildcard(_))) {
+                return Err(invalid("ORDER BY position with *"));
+            }
+            match (*n as usize).checked_sub(1).filter(|i| *i < items.len()) {
+                Some(i) => Ok(OrderKey::Output(i)),
+                None => Err(invalid(format!("ORDER BY position out of range: {}", n))),
+            }
+        }
+        Expr::Column { table: None, name } => Ok(items
+            .iter()
+            .position(|item| match item {
+                SelectItem::Expr { name: alias, .. } => alias.eq_ignore_ascii_case(name),
+                SelectItem::Wildcard(_) => false,
+            })
+            .map_or(OrderKey::Expr(expr), OrderKey::Output)),
+        _ => Ok(OrderKey::Expr(expr)),
+    }
+}
+
+/// Total order of values: NULL, numbers, text ignoring case, then bytes.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Text(_) | Value::Memo(_) => 2,
+            Value::Guid(_) => 3,
+            Value::Binary(_) | Value::Ole(_) => 4,
+            _ => 1,
+        }
+    }
+
+    match (a, b) {
+        (Value::Text(a) | Value::Memo(a), Value::Text(b) | Value::Memo(b)) => a
+            .chars()
+            .flat_map(char::to_lowercase)
+            .cmp(b.chars().flat_map(char::to_lowercase)),
+        (Value::Guid(a), Value::Guid(b)) => a.cmp(b),
+        (Value::Binary(a) | Value::Ole(a), Value::Binary(b) | Value::Ole(b)) => a.cmp(b),
+        (Value::Money(a), Value::Money(b)) => a.cmp(b),
+        _ => match (to_f64(a), to_f64(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => rank(a).cmp(&rank(b)),
+        },
+    }
+}
+
+/// Number of a numeric value; Yes is -1 in Access.
+fn to_f64(value: &Value) -> Option<f64> {
+    Some(match value {
+        Value::Bool(v) => -(*v as i32) as f64,
+        Value::Byte(v) => *v as f64,
+        Value::Int(v) => *v as f64,
+        Value::LongInt(v) => *v as f64,
+        Value::Money(v) => *v as f64 / 10_000.0,
+        Value::Float(v) => *v as f64,
+        Value::Double(v) | Value::DateTime(v) => *v,
+        Value::Numeric { value, scale, .. } => *value as f64 / 10f64.powi(*scale as i32),
+        _ => return None,
+    })
+}
+
+fn to_bool(value: &Value) -> Result<Option<bool>, Error> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(b) => Ok(Some(*b)),
+        v => to_f64(v)
+            .map(|n| Some(n != 0.0))
+            .ok_or_else(|| invalid(format!("not a condition: {:?}", v))),
+    }
+}
+
+fn from_bool(value: Option<bool>) -> Value {
+    value.map_or(Value::Null, Value::Bool)
+}
+
+/// Values compared with [`compare`], to group and deduplicate rows.
+#[derive(Debug)]
+struct Key(Vec<Value>);
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| compare(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| self.0.len().cmp(&other.0.len()))
+    }
+}
+
+/// Hashes values equal by [`compare`] alike, to join rows on their keys.
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in &self.0 {
+            match value {
+                Value::Text(s) | Value::Memo(s) => s
+                    .chars()
+                    .flat_map(char::to_lowercase)
+                    .for_each(|c| c.hash(state)),
+                Value::Guid(v) => v.hash(state),
+                Value::Binary(v) | Value::Ole(v) => v.hash(state),
+                v => to_f64(v).map(f64::to_bits).hash(state),
+            }
+        }
+    }
+}
+
+/// Columns of the rows the query is evaluated over: the columns of each
+/// table, one table after the other.
+struct Scope<'a> {
+    tables: Vec<&'a TableRef>,
+    columns: &'a [Vec<String>],
+}
+
+impl<'a> Scope<'a> {
+    /// Position of the first column of a table in the rows.
+    fn offset(&self, table: usize) -> usize {
+        self.columns[..table].iter().map(Vec::len).sum()
+    }
+
+    /// Table of a column, and its position in the rows.
+    fn locate(&self, table: Option<&str>, name: &str) -> Result<(usize, usize), Error> {
+        if let Some(table) = table.filter(|t| !self.tables.iter().any(|r| r.is_named(t))) {
+            return Err(invalid(format!("unknown table: {}", table)));
+        }
+        let mut found = None;
+        for (i, (t, columns)) in self.tables.iter().zip(self.columns).enumerate() {
+            if table.is_some_and(|table| !t.is_named(table)) {
+                continue;
+            }
+            if let Some(j) = columns.iter().position(|c| c.eq_ignore_ascii_case(name)) {
+                if found.is_some() {
+                    return Err(invalid(format!("ambiguous column: {}", name)));
+                }
+                found = Some((i, self.offset(i) + j));
+            }
+        }
+        found.ok_or_else(|| invalid(format!("unknown column: {}", name)))
+    }
+
+    fn resolve(&self, table: Option<&str>, name: &str) -> Result<usize, Error> {
+        self.locate(table, name).map(|(_, i)| i)
+    }
+
+    /// Check that the columns of `expr` exist.
+    fn check(&self, expr: &Expr) -> Result<(), Error> {
+        let mut result = Ok(());
+        expr.walk(true, &mut |e| {
+            if let Expr::Column { table, name } = e {
+                if result.is_ok() {
+                    result = self.resolve(table.as_deref(), name).map(|_| ());
+                }
+            }
+        });
+        result
+    }
+}
+
+/// Rows an expression is evaluated over: a row, or a group of rows.
+enum Context<'a> {
+    Row(&'a [Value]),
+    Group(Vec<&'a [Value]>),
+}
+
+impl Select {
+    pub fn parse(sql: &str) -> Result<Self, Error> {
+        Parser {
+            sql,
+            tokens: tokenize(sql)?,
+            pos: 0,
+        }
+        .select()
+    }
+
+    /// Whether rows are grouped, by GROUP BY or aggregates.
+    fn is_aggregate(&self) -> bool {
+        !self.group_by.is_empty()
+            || self.having.is_some()
+            || self.items.iter().any(|item| match item {
+                SelectItem::Expr { expr, .. } => expr.has_aggregate(),
+                SelectItem::Wildcard(_) => false,
+            })
+            || self.order_by.iter().any(|(key, _)| match key {
+                OrderKey::Expr(expr) => expr.has_aggregate(),
+                OrderKey::Output(_) => false,
+            })
+    }
+
+    /// Number of rows to return.
+    fn count(&self) -> Option<usize> {
+        match (self.top, self.limit) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Expressions evaluated over the rows of libmdbsql.
+    fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                SelectItem::Expr { expr, .. } => Some(expr),
+                SelectItem::Wildcard(_) => None,
+            })
+            .chain(&self.group_by)
+            .chain(&self.having)
+            .chain(self.order_by.iter().filter_map(|(key, _)| match key {
+                OrderKey::Expr(expr) => Some(expr),
+                OrderKey::Output(_) => None,
+            }))
+    }
+
+    /// Columns read from libmdbsql, or `None` for all of them.
+    fn columns(&self) -> Option<Vec<&str>> {
+        if self
+            .items
+            .iter()
+            .any(|item| matches!(item, SelectItem::Wildcard(_)))
+        {
+            return None;
+        }
+        let mut columns: Vec<&str> = vec![];
+        for expr in self.exprs() {
+            expr.walk(true, &mut |e| {
+                if let Expr::Column { name, .. } = e {
+                    if !columns.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+                        columns.push(name);
+                    }
+                }
+            });
+        }
+        Some(columns)
+    }
+
+    /// Whether the values of a column of libmdbsql's rows are used.
+    pub fn uses(&self, column: &str) -> bool {
+        self.columns()
+            .is_none_or(|c| c.iter().any(|c| c.eq_ignore_ascii_case(column)))
+    }
+
+    /// Query run by libmdbsql: the projection, WHERE clause and LIMIT.
+    pub fn pushdown(&self) -> String {
+        let columns = match self.columns().filter(|c| !c.is_empty()) {
+            Some(columns) => columns
+                .iter()
+                .map(|c| bracket(c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "*".to_string(),
+        };
+        let mut query = format!(
+            "SELECT {} FROM {}",
+            columns,
+            &self.sql[self.from.span.clone()]
+        );
+        if let Some(Filter::Pushed(filter)) = &self.filter {
+            query.push_str(" WHERE ");
+            query.push_str(&self.sql[filter.clone()]);
+        }
+        // rows are only cut before they are sorted or grouped
+        if let Some(count) = self.count() {
+            if !self.distinct && self.order_by.is_empty() && !self.is_aggregate() {
+                query.push_str(&format!(" LIMIT {}", count));
+            }
+        }
+        query
+    }
+
+    /// Whether the query joins tables.
+    pub fn is_join(&self) -> bool {
+        !self.joins.is_empty()
+    }
+
+    /// Names of the tables of the FROM clause, in join order.
+    pub fn tables(&self) -> Vec<&str> {
+        std::iter::once(&self.from)
+            .chain(self.joins.iter().map(|j| &j.table))
+            .map(|t| t.name.as_str())
+            .collect()
+    }
+
+    /// Scope of the rows of the first tables, given the columns of each.
+    fn scope<'a>(&'a self, columns: &'a [Vec<String>]) -> Scope<'a> {
+        Scope {
+            tables: std::iter::once(&self.from)
+                .chain(self.joins.iter().map(|j| &j.table))
+                .take(columns.len())
+                .collect(),
+            columns,
+        }
+    }
+
+    /// Whether the values of each column of each table are used, given the
+    /// columns of the tables of a join.
+    pub fn used_columns(&self, columns: &[Vec<String>]) -> Result<Vec<Vec<bool>>, Error> {
+        let scope = self.scope(columns);
+        let mut used: Vec<Vec<bool>> = columns.iter().map(|c| vec![false; c.len()]).collect();
+        for item in &self.items {
+            if let SelectItem::Wildcard(table) = item {
+                for (i, t) in scope.tables.iter().enumerate() {
+                    if table.as_deref().is_none_or(|table| t.is_named(table)) {
+                        used[i].fill(true);
+                    }
+                }
+            }
+        }
+
+        let filter = match &self.filter {
+            Some(Filter::Evaluated(expr)) => Some(expr),
+            _ => None,
+        };
+        // ON clauses only see the tables joined so far
+        let exprs = self.exprs().chain(filter).map(|e| (e, columns.len()));
+        let on = self.joins.iter().enumerate().map(|(i, j)| (&j.on, i + 2));
+        for (expr, tables) in exprs.chain(on) {
+            let scope = self.scope(&columns[..tables]);
+            let mut result = Ok(());
+            expr.walk(true, &mut |e| {
+                if let Expr::Column { table, name } = e {
+                    match scope.locate(table.as_deref(), name) {
+                        Ok((t, i)) => used[t][i - scope.offset(t)] = true,
+                        Err(e) if result.is_ok() => result = Err(e),
+                        Err(_) => {}
+                    }
+                }
+            });
+            result?;
+        }
+        Ok(used)
+    }
+
+    /// Keys of the join of the `i`-th table, from the equalities of its ON
+    /// clause, and the other conditions of the clause.
+    fn join_keys<'a>(
+        &'a self,
+        i: usize,
+        scope: &Scope,
+    ) -> Result<(JoinKeys, Vec<&'a Expr>), Error> {
+        fn conjuncts<'a>(expr: &'a Expr, found: &mut Vec<&'a Expr>) {
+            match expr {
+                Expr::And(left, right) => {
+                    conjuncts(left, found);
+                    conjuncts(right, found);
+                }
+                expr => found.push(expr),
+            }
+        }
+
+        let mut exprs = vec![];
+        conjuncts(&self.joins[i - 1].on, &mut exprs);
+        let offset = scope.offset(i);
+        let mut keys = vec![];
+        let mut conditions = vec![];
+        for expr in exprs {
+            scope.check(expr)?;
+            if let Expr::Compare(CompareOp::Eq, left, right) = expr {
+                if let (
+                    Expr::Column {
+                        table: left_table,
+                        name: left_name,
+                    },
+                    Expr::Column {
+                        table: right_table,
+                        name: right_name,
+                    },
+                ) = (left.as_ref(), right.as_ref())
+                {
+                    let left = scope.locate(left_table.as_deref(), left_name)?;
+                    let right = scope.locate(right_table.as_deref(), right_name)?;
+                    match (left.0 == i, right.0 == i) {
+                        (false, true) => {
+                            keys.push((left.1, right.1 - offset));
+                            continue;
+                        }
+                        (true, false) => {
+                            keys.push((right.1, left.1 - offset));
+                            continue;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            conditions.push(expr);
+        }
+        Ok((keys, conditions))
+    }
+
+    /// Join the rows of the `i`-th table to the rows of the tables before it.
+    ///
+    /// Rows are matched by a hash of the equalities of the ON clause, NULL
+    /// never being equal, and the other conditions are tested on each match.
+    pub fn join(
+        &self,
+        i: usize,
+        columns: &[Vec<String>],
+        left: Vec<Vec<Value>>,
+        right: Vec<Vec<Value>>,
+    ) -> Result<Vec<Vec<Value>>, Error> {
+        let scope = self.scope(&columns[..=i]);
+        let (keys, conditions) = self.join_keys(i, &scope)?;
+        let width = scope.offset(i) + columns[i].len();
+
+        let mut hashed: HashMap<Key, Vec<usize>> = HashMap::new();
+        for (r, row) in right.iter().enumerate() {
+            let key: Vec<Value> = keys.iter().map(|(_, k)| row[*k].clone()).collect();
+            if !key.iter().any(Value::is_null) {
+                hashed.entry(Key(key)).or_default().push(r);
+            }
+        }
+        // without equalities, every row is a candidate
+        let all: Vec<usize> = (0..right.len()).collect();
+
+        let mut rows = vec![];
+        for row in left {
+            let candidates = match keys.is_empty() {
+                true => &all,
+                false => {
+                    let key = keys.iter().map(|(k, _)| row[*k].clone()).collect();
+                    match hashed.get(&Key(key)) {
+                        Some(candidates) => candidates,
+                        None => &vec![],
+                    }
+                }
+            };
+
+            let mut matched = false;
+            'candidates: for r in candidates {
+                let mut joined = row.clone();
+                joined.extend(right[*r].iter().cloned());
+                for condition in &conditions {
+                    let value = self.eval(&scope, condition, &Context::Row(&joined))?;
+                    if to_bool(&value)? != Some(true) {
+                        continue 'candidates;
+                    }
+                }
+                rows.push(joined);
+                matched = true;
+            }
+            if !matched && self.joins[i - 1].kind == JoinKind::Left {
+                let mut padded = row;
+                padded.resize(width, Value::Null);
+                rows.push(padded);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Names and expressions of the columns of the result.
+    fn outputs(&self, scope: &Scope) -> Result<(Vec<String>, Vec<Expr>), Error> {
+        let mut names = vec![];
+        let mut outputs = vec![];
+        for item in &self.items {
+            match item {
+                SelectItem::Wildcard(table) => {
+                    if let Some(table) = table
+                        .as_deref()
+                        .filter(|t| !scope.tables.iter().any(|r| r.is_named(t)))
+                    {
+                        return Err(invalid(format!("unknown table: {}", table)));
+                    }
+                    for (t, columns) in scope.tables.iter().zip(scope.columns) {
+                        if table.as_deref().is_some_and(|table| !t.is_named(table)) {
+                            continue;
+                        }
+                        // qualified, as joined tables may share column names
+                        let qualifier = t.alias.as_ref().unwrap_or(&t.name);
+                        for column in columns {
+                            names.push(column.clone());
+                            outputs.push(Expr::Column {
+                                table: Some(qualifier.clone()),
+                                name: column.clone(),
+                            });
+                        }
+                    }
+                }
+                SelectItem::Expr { expr, name } => {
+                    names.push(name.clone());
+                    outputs.push(expr.clone());
+                }
+            }
+        }
+        Ok((names, outputs))
+    }
+
+    /// Names of the columns of the result, given the columns of each table.
+    pub fn column_names(&self, columns: &[Vec<String>]) -> Result<Vec<String>, Error> {
+        let scope = self.scope(columns);
+        for expr in self.exprs() {
+            scope.check(expr)?;
+        }
+        Ok(self.outputs(&scope)?.0)
+    }
+
+    /// Evaluate the query over the rows returned by [`Select::pushdown`], or
+    /// over joined rows, given the columns of each table.
+    pub fn evaluate(
+        &self,
+        columns: &[Vec<String>],
+        mut rows: Vec<Vec<Value>>,
+    ) -> Result<ResultSet, Error> {
+        let scope = self.scope(columns);
+        for expr in self.exprs() {
+            scope.check(expr)?;
+        }
+        if let Some(Filter::Evaluated(filter)) = &self.filter {
+            scope.check(filter)?;
+            let mut kept = Vec::with_capacity(rows.len());
+            for row in rows {
+                if to_bool(&self.eval(&scope, filter, &Context::Row(&row))?)? == Some(true) {
+                    kept.push(row);
+                }
+            }
+            rows = kept;
+        }
+        for expr in &self.group_by {
+            if expr.has_aggregate() {
+                return Err(invalid("aggregate in GROUP BY"));
+            }
+        }
+
+        let (names, outputs) = self.outputs(&scope)?;
+
+        let mut results = vec![];
+        if self.is_aggregate() {
+            let grouped: Vec<usize> = self
+                .group_by
+                .iter()
+                .filter_map(|e| match e {
+                    Expr::Column { table, name } => scope.resolve(table.as_deref(), name).ok(),
+                    _ => None,
+                })
+                .collect();
+            for expr in outputs
+                .iter()
+                .chain(&self.having)
+                .chain(self.order_by.iter().filter_map(|(key, _)| match key {
+                    OrderKey::Expr(expr) => Some(expr),
+                    OrderKey::Output(_) => None,
+                }))
+            {
+                let mut ungrouped = None;
+                expr.walk(false, &mut |e| {
+                    if let Expr::Column { table, name } = e {
+                        let i = scope.resolve(table.as_deref(), name).ok();
+                        if !i.is_some_and(|i| grouped.contains(&i)) {
+                            ungrouped.get_or_insert(name);
+                        }
+                    }
+                });
+                if let Some(name) = ungrouped {
+                    return Err(invalid(format!(
+                        "column {} is neither grouped nor aggregated",
+                        name
+                    )));
+                }
+            }
+
+            let mut groups: BTreeMap<Key, Vec<&[Value]>> = BTreeMap::new();
+            if self.group_by.is_empty() {
+                // one group, even without rows
+                groups.insert(Key(vec![]), rows.iter().map(Vec::as_slice).collect());
+            } else {
+                for row in &rows {
+                    let key = self
+                        .group_by
+                        .iter()
+                        .map(|e| self.eval(&scope, e, &Context::Row(row)))
+                        .collect::<Result<_, _>>()?;
+                    groups.entry(Key(key)).or_default().push(row);
+                }
+            }
+
+            for group in groups.into_values() {
+                let context = Context::Group(group);
+                if let Some(having) = &self.having {
+                    if to_bool(&self.eval(&scope, having, &context)?)? != Some(true) {
+                        continue;
+                    }
+                }
+                results.push(self.output(&scope, &outputs, &context)?);
+            }
+        } else {
+            for row in &rows {
+                results.push(self.output(&scope, &outputs, &Context::Row(row))?);
+            }
+        }
+
+        if self.distinct {
+            let mut seen = BTreeSet::new();
+            results.retain(|(row, _)| seen.insert(Key(row.clone())));
+        }
+
+        results.sort_by(|(_, a), (_, b)| {
+            a.iter()
+                .zip(b)
+                .zip(&self.order_by)
+                .map(|((a, b), (_, order))| match order {
+                    SortOrder::Ascending => compare(a, b),
+                    SortOrder::Descending => compare(b, a),
+                })
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        if let Some(count) = self.count() {
+            results.truncate(count);
+        }
+
+        Ok(ResultSet {
+            columns: names,
+            rows: results.into_iter().map(|(row, _)| row).collect(),
+        })
+    }
+
+    /// Values of a row of the result, with its ORDER BY keys.
+    fn output(
+        &self,
+        scope: &Scope,
+        outputs: &[Expr],
+        context: &Context,
+    ) -> Result<(Vec<Value>, Vec<Value>), Error> {
+        let row = outputs
+            .iter()
+            .map(|e| self.eval(scope, e, context))
+            .collect::<Result<Vec<_>, _>>()?;
+        let keys = self
+            .order_by
+            .iter()
+            .map(|(key, _)| match key {
+                OrderKey::Output(i) => Ok(row[*i].clone()),
+                OrderKey::Expr(e) => self.eval(scope, e, context),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((row, keys))
+    }
+
+    fn eval(&self, scope: &Scope, expr: &Expr, context: &Context) -> Result<Value, Error> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Column { table, name } => {
+                let i = scope.resolve(table.as_deref(), name)?;
+                match context {
+                    Context::Row(row) => row[i].clone(),
+                    // grouped columns are the same in every row
+                    Context::Group(rows) => rows.first().map_or(Value::Null, |r| r[i].clone()),
+                }
+            }
+            Expr::Aggregate(function, arg) => {
+                let rows = match context {
+                    Context::Group(rows) => rows,
+                    Context::Row(_) => return Err(invalid("aggregate outside of a group")),
+                };
+                let arg = match arg {
+                    Some(arg) => arg,
+                    None => return Ok(Value::LongInt(rows.len() as i32)),
+                };
+                let values = rows
+                    .iter()
+                    .map(|row| self.eval(scope, arg, &Context::Row(row)))
+                    .filter(|v| !matches!(v, Ok(Value::Null)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                aggregate(*function, values)?
+            }
+            Expr::Compare(op, left, right) => {
+                let left = self.eval(scope, left, context)?;
+                let right = self.eval(scope, right, context)?;
+                if left.is_null() || right.is_null() {
+                    Value::Null
+                } else {
+                    Value::Bool(op.test(compare(&left, &right)))
+                }
+            }
+            Expr::IsNull(expr, negated) => {
+                Value::Bool(self.eval(scope, expr, context)?.is_null() != *negated)
+            }
+            Expr::And(left, right) => {
+                let left = to_bool(&self.eval(scope, left, context)?)?;
+                let right = to_bool(&self.eval(scope, right, context)?)?;
+                from_bool(match (left, right) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                })
+            }
+            Expr::Or(left, right) => {
+                let left = to_bool(&self.eval(scope, left, context)?)?;
+                let right = to_bool(&self.eval(scope, right, context)?)?;
+                from_bool(match (left, right) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                })
+            }
+            Expr::Not(expr) => from_bool(to_bool(&self.eval(scope, expr, context)?)?.map(|b| !b)),
+        })
+    }
+}
+
+/// Aggregate of the non-NULL values of a group.
+fn aggregate(function: Aggregate, values: Vec<Value>) -> Result<Value, Error> {
+    if function == Aggregate::Count {
+        return Ok(Value::LongInt(values.len() as i32));
+    }
+    if values.is_empty() {
+        return Ok(Value::Null);
+    }
+
+    let min_max = |ordering| {
+        values
+            .iter()
+            .reduce(|a, b| if compare(b, a) == ordering { b } else { a })
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    let numbers = || {
+        values
+            .iter()
+            .map(|v| to_f64(v).ok_or_else(|| invalid(format!("not a number: {:?}", v))))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    Ok(match function {
+        Aggregate::Min => min_max(Ordering::Less),
+        Aggregate::Max => min_max(Ordering::Greater),
+        Aggregate::Avg => Value::Double(numbers()?.iter().sum::<f64>() / values.len() as f64),
+        Aggregate::Sum => {
+            if values.iter().all(|v| matches!(v, Value::Money(_))) {
+                Value::Money(
+                    values
+                        .iter()
+                        .map(|v| match v {
+                            Value::Money(m) => *m,
+                            _ => 0,
+                        })
+                        .sum(),
+                )
+            } else if values
+                .iter()
+                .all(|v| matches!(v, Value::Byte(_) | Value::Int(_) | Value::LongInt(_)))
+            {
+                let sum: i64 = numbers()?.iter().map(|n| *n as i64).sum();
+                i32::try_from(sum).map_or(Value::Double(sum as f64), Value::LongInt)
+            } else {
+                Value::Double(numbers()?.iter().sum())
+            }
+        }
+        Aggregate::Count => unreachable!(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    /// Columns and rows of a table of orders, customers or cities.
+    fn table(name: &str) -> (Vec<String>, Vec<Vec<Value>>) {
+        let columns = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        match name {
+            "Customers" => (
+                columns(&["Name", "City"]),
+                vec![
+                    vec![text("bob"), Value::LongInt(1)],
+                    vec![text("Alice"), Value::LongInt(2)],
+                    vec![text("Dave"), Value::LongInt(1)],
+                ],
+            ),
+            "Cities" => (
+                columns(&["ID", "Name"]),
+                vec![
+                    vec![Value::Int(1), text("Oslo")],
+                    vec![Value::Int(2), text("Rome")],
+                ],
+            ),
+            _ => (columns(&["ID", "Customer", "Amount", "Shipped"]), orders()),
+        }
+    }
+
+    fn orders() -> Vec<Vec<Value>> {
+        vec![
+            vec![
+                Value::LongInt(1),
+                text("Bob"),
+                Value::Money(105_000),
+                Value::Bool(true),
+            ],
+            vec![
+                Value::LongInt(2),
+                text("alice"),
+                Value::Money(20_000),
+                Value::Bool(false),
+            ],
+            vec![
+                Value::LongInt(3),
+                text("Alice"),
+                Value::Money(35_000),
+                Value::Bool(true),
+            ],
+            vec![
+                Value::LongInt(4),
+                text("Carol"),
+                Value::Null,
+                Value::Bool(false),
+            ],
+            vec![
+                Value::LongInt(5),
+                text("Bob"),
+                Value::Money(10_000),
+                Value::Bool(true),
+            ],
+        ]
+    }
+
+    /// Rows of a query of the table of orders.
+    fn evaluate(sql: &str) -> Result<ResultSet, Error> {
+        let (columns, rows) = table("Orders");
+        Select::parse(sql)?.evaluate(&[columns], rows)
+    }
+
+    /// Rows of a query joining tables, read in full.
+    fn join(sql: &str) -> Result<ResultSet, Error> {
+        let select = Select::parse(sql)?;
+        let (columns, rows): (Vec<_>, Vec<_>) = select.tables().into_iter().map(table).unzip();
+        select.used_columns(&columns)?;
+
+        let mut rows = rows.into_iter();
+        let mut joined = rows.next().unwrap_or_default();
+        for (i, right) in rows.enumerate() {
+            joined = select.join(i + 1, &columns, joined, right)?;
+        }
+        select.evaluate(&columns, joined)
+    }
+
+    #[test]
+    fn pushdown() {
+        let select = Select::parse(
+            "SELECT Customer, SUM(Amount) AS Total FROM [Orders] o \
+             WHERE (Shipped = 1 OR ID > 2) GROUP BY o.Customer ORDER BY Total DESC",
+        )
+        .unwrap();
+        assert_eq!(
+            select.pushdown(),
+            "SELECT [Customer], [Amount] FROM [Orders] WHERE (Shipped = 1 OR ID > 2)"
+        );
+        assert!(select.uses("customer"));
+        assert!(!select.uses("Shipped"));
+
+        let select = Select::parse("select top 5 * from Orders where ID > 1 limit 3;").unwrap();
+        assert_eq!(
+            select.pushdown(),
+            "SELECT * FROM Orders WHERE ID > 1 LIMIT 3"
+        );
+        let select = Select::parse("select COUNT(*) from Orders").unwrap();
+        assert_eq!(select.pushdown(), "SELECT * FROM Orders");
+        let select = Select::parse("select ID from Orders order by ID limit 2").unwrap();
+        assert_eq!(select.pushdown(), "SELECT [ID] FROM Orders");
+        let select = Select::parse("select [a]]b] from [x]]y]").unwrap();
+        assert_eq!(select.tables(), vec!["x]y"]);
+        assert_eq!(select.pushdown(), "SELECT [a]]b] FROM [x]]y]");
+    }
+
+    #[test]
+    fn order_by() {
+        let result =
+            evaluate("SELECT ID, Customer FROM Orders ORDER BY Customer, ID DESC").unwrap();
+        assert_eq!(result.columns, vec!["ID", "Customer"]);
+        let ids: Vec<Value> = result.rows.iter().map(|r| r[0].clone()).collect();
+        assert_eq!(
+            ids,
+            [3, 2, 5, 1, 4].map(Value::LongInt).to_vec(),
+            "text is sorted ignoring case"
+        );
+
+        let result = evaluate("SELECT ID FROM Orders ORDER BY Amount LIMIT 2").unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![Value::LongInt(4)], vec![Value::LongInt(5)]]
+        );
+
+        let result = evaluate("SELECT ID, Amount AS a FROM Orders ORDER BY 2 DESC, a").unwrap();
+        assert_eq!(result.rows[0][0], Value::LongInt(1));
+    }
+
+    #[test]
+    fn group_by() {
+        let result = evaluate(
+            "SELECT Customer, COUNT(*) AS n, SUM(Amount), MAX(ID) FROM Orders \
+             GROUP BY Customer HAVING COUNT(Amount) > 0 ORDER BY SUM(Amount) DESC",
+        )
+        .unwrap();
+        assert_eq!(
+            result.columns,
+            vec!["Customer", "n", "SUM(Amount)", "MAX(ID)"]
+        );
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![
+                    text("Bob"),
+                    Value::LongInt(2),
+                    Value::Money(115_000),
+                    Value::LongInt(5)
+                ],
+                vec![
+                    text("alice"),
+                    Value::LongInt(2),
+                    Value::Money(55_000),
+                    Value::LongInt(3)
+                ],
+            ]
+        );
+        assert_eq!(result.column_index("N"), Some(1));
+
+        let result = evaluate("SELECT COUNT(Amount), AVG(ID), MIN(Customer) FROM Orders").unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![Value::LongInt(4), Value::Double(3.0), text("alice")]]
+        );
+
+        let result = evaluate("SELECT COUNT(*), SUM(ID) FROM Orders WHERE ID > 5").unwrap();
+        assert_eq!(result.rows.len(), 1);
+
+        assert!(matches!(
+            evaluate("SELECT Customer, ID FROM Orders GROUP BY Customer"),
+            Err(Error::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            evaluate("SELECT SUM(Customer) FROM Orders"),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn distinct() {
+        let result = evaluate("SELECT DISTINCT Customer FROM Orders ORDER BY Customer").unwrap();
+        assert_eq!(
+            result.rows,
+            vec![vec![text("alice")], vec![text("Bob")], vec![text("Carol")]]
+        );
+        let result = evaluate("SELECT DISTINCT Shipped FROM Orders").unwrap();
+        assert_eq!(result.rows.len(), 2);
+    }
+
+    #[test]
+    fn invalid_query() {
+        for sql in [
+            "SELECT FROM Orders",
+            "SELECT * FROM Orders WHERE",
+            "SELECT * FROM Orders ORDER BY 3",
+            "SELECT ID FROM Orders GROUP",
+            "SELECT LEN(Customer) FROM Orders",
+            "SELECT ID FROM Orders LIMIT x",
+            "SELECT 'ID FROM Orders",
+        ] {
+            assert!(
+                matches!(Select::parse(sql), Err(Error::InvalidQuery(_))),
+                "{}",
+                sql
+            );
+        }
+        assert!(matches!(
+            evaluate("SELECT Name FROM Orders"),
+            Err(Error::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            evaluate("SELECT x.ID FROM Orders"),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn inner_join() {
+        let result = join(
+            "SELECT o.ID, c.City FROM Orders o INNER JOIN Customers c ON o.Customer = c.Name \
+             ORDER BY o.ID",
+        )
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            [(1, 1), (2, 2), (3, 2), (5, 1)]
+                .map(|(a, b)| vec![Value::LongInt(a), Value::LongInt(b)])
+                .to_vec(),
+            "text keys are matched ignoring case"
+        );
+
+        // nested as in Access, with a condition besides the key
+        let result = join(
+            "SELECT t.Name, COUNT(*) FROM (Orders INNER JOIN Customers \
+             ON Orders.Customer = Customers.Name AND Orders.Amount > 1) \
+             INNER JOIN Cities t ON Customers.City = t.ID GROUP BY t.Name ORDER BY t.Name",
+        )
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![text("Oslo"), Value::LongInt(1)],
+                vec![text("Rome"), Value::LongInt(2)],
+            ]
+        );
+
+        let result =
+            join("SELECT * FROM Customers JOIN Cities ON Customers.City = Cities.ID").unwrap();
+        assert_eq!(result.columns, vec!["Name", "City", "ID", "Name"]);
+        assert_eq!(result.rows.len(), 3);
+        let result =
+            join("SELECT c.*, o.ID FROM Customers c JOIN Orders o ON o.Customer = c.Name").unwrap();
+        assert_eq!(result.columns, vec!["Name", "City", "ID"]);
+    }
+
+    #[test]
+    fn left_join() {
+        let result = join(
+            "SELECT o.ID, c.Name FROM Orders o LEFT OUTER JOIN Customers c \
+             ON o.Customer = c.Name WHERE c.Name IS NULL OR o.ID = 1",
+        )
+        .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![Value::LongInt(1), text("bob")],
+                vec![Value::LongInt(4), Value::Null],
+            ]
+        );
+
+        let result = join(
+            "SELECT c.Name, COUNT(o.ID) FROM Customers c LEFT JOIN Orders o \
+             ON c.Name = o.Customer GROUP BY c.Name ORDER BY 2, 1",
+        )
+        .unwrap();
+        assert_eq!(result.rows[0], vec![text("Dave"), Value::LongInt(0)]);
+    }
+
+    #[test]
+    fn invalid_join() {
+        for sql in [
+            "SELECT * FROM Orders RIGHT JOIN Customers ON Customer = Name",
+            "SELECT * FROM Orders JOIN Orders ON Orders.ID = Orders.ID",
+            "SELECT * FROM Orders JOIN Customers",
+            "SELECT * FROM (Orders JOIN Customers ON Customer = Name",
+            "SELECT * FROM Orders JOIN Customers ON COUNT(*) > 1",
+        ] {
+            assert!(
+                matches!(Select::parse(sql), Err(Error::InvalidQuery(_))),
+                "{}",
+                sql
+            );
+        }
+        for sql in [
+            "SELECT ID FROM Orders JOIN Customers ON Customer = Name JOIN Cities ON City = ID",
+            "SELECT * FROM Orders o JOIN Customers c ON o.Customer = t.Name \
+             JOIN Cities t ON c.City = t.ID",
+        ] {
+            assert!(matches!(join(sql), Err(Error::InvalidQuery(_))), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn column_names() {
+        let names = |sql: &str| {
+            let select = Select::parse(sql)?;
+            let columns: Vec<Vec<String>> = select.tables().iter().map(|t| table(t).0).collect();
+            select.column_names(&columns)
+        };
+        assert_eq!(
+            names("SELECT Amount AS Total, ID FROM Orders WHERE ID = NULL").unwrap(),
+            vec!["Total", "ID"]
+        );
+        assert_eq!(
+            names("SELECT c.*, Amount FROM Orders JOIN Customers c ON Customer = c.Name").unwrap(),
+            vec!["Name", "City", "Amount"]
+        );
+        assert!(matches!(
+            names("SELECT Total FROM Orders"),
+            Err(Error::InvalidQuery(_))
+        ));
+    }
+}
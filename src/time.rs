@@ -0,0 +1,110 @@
+use time::{Date, PrimitiveDateTime, Time};
+
+use crate::error::Error;
+use crate::params::{quote, ToSql};
+use crate::value::{DateTime, Value};
+
+impl TryFrom<&Value> for PrimitiveDateTime {
+    type Error = Error;
+
+    /// Converts Date/Time values from their days since 1899-12-30.
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        let dt = DateTime::try_from(value)?;
+        let (hour, minute, second, milli) = dt.time();
+        let date = i32::try_from(dt.year)
+            .ok()
+            .and_then(|year| Date::from_ordinal_date(year, dt.ordinal as u16).ok());
+        let time = Time::from_hms_milli(hour as u8, minute as u8, second as u8, milli as u16).ok();
+        match (date, time) {
+            (Some(date), Some(time)) => Ok(PrimitiveDateTime::new(date, time)),
+            _ => Err(Error::InvalidDateTime(dt.iso())),
+        }
+    }
+}
+
+impl TryFrom<&Value> for Date {
+    type Error = Error;
+
+    /// Converts the date of Date/Time values.
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        PrimitiveDateTime::try_from(value).map(|dt| dt.date())
+    }
+}
+
+impl ToSql for PrimitiveDateTime {
+    fn to_sql(&self) -> Result<String, Error> {
+        Ok(quote(&format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year(),
+            self.month() as u8,
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second()
+        )))
+    }
+}
+
+impl ToSql for Date {
+    fn to_sql(&self) -> Result<String, Error> {
+        self.midnight().to_sql()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Connection;
+    use time::Month;
+
+    #[test]
+    fn datetime() {
+        let conn = Connection::open("resource/test.mdb").unwrap();
+        let dates: Vec<(PrimitiveDateTime, Date, Option<PrimitiveDateTime>)> = conn
+            .prepare("select D from Table1")
+            .unwrap()
+            .map(|r| Ok((r.get(0)?, r.get(0)?, r.get(0)?)))
+            .collect::<Result<_, Error>>()
+            .unwrap();
+
+        let date = |y| Date::from_calendar_date(y, Month::January, 1).unwrap();
+        assert_eq!(
+            dates,
+            vec![
+                (
+                    date(2000).midnight(),
+                    date(2000),
+                    Some(date(2000).midnight())
+                ),
+                (
+                    date(1999).midnight(),
+                    date(1999),
+                    Some(date(1999).midnight())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from() {
+        let value = Value::DateTime(36526.75);
+        assert_eq!(
+            PrimitiveDateTime::try_from(&value).unwrap(),
+            Date::from_calendar_date(2000, Month::January, 1)
+                .unwrap()
+                .with_hms(18, 0, 0)
+                .unwrap()
+        );
+        assert!(Date::try_from(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn to_sql() {
+        let date = Date::from_calendar_date(2000, Month::January, 2).unwrap();
+        assert_eq!(date.to_sql().unwrap(), "'2000-01-02 00:00:00'");
+        assert_eq!(
+            date.with_hms(3, 4, 5).unwrap().to_sql().unwrap(),
+            "'2000-01-02 03:04:05'"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::params::{quote, ToSql};
+
+/// Renders the braced form libmdb compares Replication IDs with.
+impl ToSql for Uuid {
+    fn to_sql(&self) -> Result<String, Error> {
+        Ok(quote(&format!("{{{}}}", self.hyphenated()).to_uppercase()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Connection, GuidFormat};
+
+    #[test]
+    fn replication_id() {
+        let mut conn = Connection::open("resource/test.mdb").unwrap();
+        let guid = Uuid::parse_str("2b480f44-fa67-41d7-abe9-4d2f0e2c9087").unwrap();
+
+        let row = conn
+            .prepare("select GUID from MSysNameMap")
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(row.get::<Uuid>(0).unwrap(), guid);
+        assert_eq!(
+            row.get::<String>(0).unwrap(),
+            "{2B480F44-FA67-41D7-ABE9-4D2F0E2C9087}"
+        );
+
+        conn.set_guid_format(GuidFormat::Hyphenated);
+        let row = conn
+            .prepare("select GUID from MSysNameMap")
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(row.get::<String>(0).unwrap(), guid.to_string());
+    }
+    #[test]
+    fn to_sql() {
+        let guid = Uuid::parse_str("2b480f44-fa67-41d7-abe9-4d2f0e2c9087").unwrap();
+        assert_eq!(
+            guid.to_sql().unwrap(),
+            "'{2B480F44-FA67-41D7-ABE9-4D2F0E2C9087}'"
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use std::sync::Mutex;
+
+use libc::c_int;
+use libmdb_sys::{
+    MDB_BINARY, MDB_BOOL, MDB_BYTE, MDB_COMPLEX, MDB_DATETIME, MDB_DOUBLE, MDB_FLOAT, MDB_INT,
+    MDB_LONGINT, MDB_MEMO, MDB_MONEY, MDB_NUMERIC, MDB_OLE, MDB_REPID, MDB_TEXT,
+};
+
+use crate::error::Error;
+use crate::ffi::{Mdb, SqlColumn, SqlValue};
+
+/// Typed value of a cell, following the Access column type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// SQL NULL.
+    Null,
+    /// Yes/No.
+    Bool(bool),
+    /// Byte.
+    Byte(u8),
+    /// Integer.
+    Int(i16),
+    /// Long Integer.
+    LongInt(i32),
+    /// Currency, as a fixed-point number scaled by 10 000.
+    Money(i64),
+    /// Single.
+    Float(f32),
+    /// Double.
+    Double(f64),
+    /// Date/Time, as days since 1899-12-30.
+    DateTime(f64),
+    /// Text.
+    Text(String),
+    /// Memo.
+    Memo(String),
+    /// Binary.
+    Binary(Vec<u8>),
+    /// OLE Object.
+    Ole(Vec<u8>),
+    /// Replication ID.
+    Guid([u8; 16]),
+    /// Decimal, as an unscaled integer with its precision and scale.
+    Numeric {
+        /// Unscaled value.
+        value: i128,
+        /// Total number of digits.
+        precision: u8,
+        /// Number of digits after the decimal point.
+        scale: u8,
+    },
+}
+
+impl Value {
+    /// Decode a value copied from a row of `mdb`.
+    ///
+    /// The database is only locked to read OLE objects.
+    pub(crate) fn from_sql(
+        mdb: &Mutex<Mdb>,
+        column: &SqlColumn,
+        value: &SqlValue,
+    ) -> Result<Self, Error> {
+        let invalid = || Error::InvalidColumnData(column.name());
+
+        if value.is_invalid() {
+            return Err(invalid());
+        }
+        let data = match value.data() {
+            Some(data) => data,
+            None => return Ok(Value::Null),
+        };
+
+        let value = match column.col_type() as u32 {
+            MDB_BOOL => Value::Bool(data.first().is_some_and(|b| *b != 0)),
+            MDB_BYTE => Value::Byte(*data.first().ok_or_else(invalid)?),
+            MDB_INT => Value::Int(i16::from_le_bytes(fixed(data).ok_or_else(invalid)?)),
+            MDB_LONGINT | MDB_COMPLEX => {
+                Value::LongInt(i32::from_le_bytes(fixed(data).ok_or_else(invalid)?))
+            }
+            MDB_MONEY => Value::Money(i64::from_le_bytes(fixed(data).ok_or_else(invalid)?)),
+            MDB_FLOAT => Value::Float(f32::from_le_bytes(fixed(data).ok_or_else(invalid)?)),
+            MDB_DOUBLE => Value::Double(f64::from_le_bytes(fixed(data).ok_or_else(invalid)?)),
+            MDB_DATETIME => Value::DateTime(f64::from_le_bytes(fixed(data).ok_or_else(invalid)?)),
+            MDB_TEXT => Value::Text(value.get()?.to_string()),
+            MDB_MEMO => Value::Memo(value.get()?.to_string()),
+            MDB_BINARY => Value::Binary(data.to_vec()),
+            MDB_OLE => Value::Ole(mdb.lock()?.ole_read(data)),
+            MDB_REPID => Value::Guid(fixed(data).ok_or_else(invalid)?),
+            MDB_NUMERIC => {
+                let bytes: [u8; 17] = fixed(data).ok_or_else(invalid)?;
+                // 1 sign byte, followed by four little-endian 32-bit words
+                // from the most significant one.
+                let value = bytes[1..].chunks(4).fold(0i128, |acc, word| {
+                    (acc << 32) | u32::from_le_bytes([word[0], word[1], word[2], word[3]]) as i128
+                });
+                Value::Numeric {
+                    value: if bytes[0] & 0x80 != 0 { -value } else { value },
+                    precision: column.col_prec() as u8,
+                    scale: column.col_scale() as u8,
+                }
+            }
+            _ => Value::Text(value.get()?.to_string()),
+        };
+
+        Ok(value)
+    }
+
+    /// Whether the value is SQL NULL.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+/// Text format of Replication ID values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GuidFormat {
+    /// `{2B480F44-FA67-41D7-ABE9-4D2F0E2C9087}`, as rendered by libmdb.
+    #[default]
+    Braced,
+    /// `2b480f44-fa67-41d7-abe9-4d2f0e2c9087`
+    Hyphenated,
+    /// `2b480f44fa6741d7abe94d2f0e2c9087`
+    Simple,
+}
+
+impl GuidFormat {
+    /// Format the raw bytes of a Replication ID.
+    pub fn format(&self, guid: &[u8; 16]) -> String {
+        // GUIDs are stored with the first three groups in little-endian.
+        let mut bytes = *guid;
+        bytes[..4].reverse();
+        bytes[4..6].reverse();
+        bytes[6..8].reverse();
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        match self {
+            GuidFormat::Braced => format!(
+                "{{{}-{}-{}-{}-{}}}",
+                &hex[..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..]
+            )
+            .to_uppercase(),
+            GuidFormat::Hyphenated => format!(
+                "{}-{}-{}-{}-{}",
+                &hex[..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..]
+            ),
+            GuidFormat::Simple => hex,
+        }
+    }
+
+    /// Replace the text of a Replication ID cell.
+    pub(crate) fn apply(&self, column: &SqlColumn, value: &mut SqlValue) {
+        if column.col_type() != MDB_REPID as c_int {
+            return;
+        }
+        if let Some(guid) = value.data().and_then(fixed) {
+            value.set_text(self.format(&guid));
+        }
+    }
+}
+
+/// Reference to a cell of a row.
+pub struct ValueRef<'a> {
+    mdb: &'a Mutex<Mdb>,
+    column: &'a SqlColumn,
+    value: &'a SqlValue,
+}
+
+impl<'a> ValueRef<'a> {
+    pub(crate) fn new(mdb: &'a Mutex<Mdb>, column: &'a SqlColumn, value: &'a SqlValue) -> Self {
+        Self { mdb, column, value }
+    }
+
+    /// Text of the cell as rendered by libmdb.
+    pub fn as_str(&self) -> Result<&'a str, Error> {
+        self.value.get()
+    }
+
+    /// Whether the cell is NULL.
+    pub fn is_null(&self) -> bool {
+        !self.value.is_invalid() && self.value.data().is_none()
+    }
+
+    /// Typed value of the cell.
+    pub fn value(&self) -> Result<Value, Error> {
+        Value::from_sql(self.mdb, self.column, self.value)
+    }
+}
+
+/// Split an Access date/time into days since 1899-12-30 and milliseconds
+/// since midnight, or `None` if it is outside the range Access supports.
+pub(crate) fn split_datetime(value: f64) -> Option<(i64, i64)> {
+    // 0100-01-01 to 9999-12-31
+    if !(-657_434.0..2_958_466.0).contains(&value) {
+        return None;
+    }
+    let days = value.trunc();
+    // The fraction is the time of day, also for dates before 1899-12-30.
+    let millis = ((value - days).abs() * 86_400_000.0).round();
+    Some((days as i64, millis as i64))
+}
+
+/// Calendar fields of an Access date/time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    /// Day of the year, from 1.
+    pub ordinal: u32,
+    /// Milliseconds since midnight.
+    pub millis: u32,
+}
+
+impl DateTime {
+    /// Calendar fields of days since 1899-12-30, or `None` if the value is
+    /// outside the range Access supports.
+    pub(crate) fn from_days(value: f64) -> Option<Self> {
+        let (days, millis) = split_datetime(value)?;
+        // Rounding can carry the time of day over to the next day.
+        let days = days + millis / 86_400_000;
+        let millis = (millis % 86_400_000) as u32;
+
+        // Civil date from days since 0000-03-01, in 400-year eras.
+        let z = days + 693_899;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + (month <= 2) as i64;
+
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let ordinal = match month {
+            1 | 2 => (doy - 305) as u32,
+            _ => (doy + 60 + leap as i64) as u32,
+        };
+
+        Some(Self {
+            year,
+            month,
+            day,
+            ordinal,
+            millis,
+        })
+    }
+
+    /// Hour, minute, second and millisecond of the time of day.
+    pub(crate) fn time(&self) -> (u32, u32, u32, u32) {
+        let secs = self.millis / 1000;
+        (secs / 3600, secs / 60 % 60, secs % 60, self.millis % 1000)
+    }
+
+    /// ISO 8601 text, with milliseconds if there are any.
+    pub(crate) fn iso(&self) -> String {
+        let (hour, minute, second, _) = self.time();
+        let text = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, hour, minute, second
+        );
+        match self.millis % 1000 {
+            0 => text,
+            millis => format!("{}.{:03}", text, millis),
+        }
+    }
+}
+
+impl TryFrom<&Value> for DateTime {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::DateTime(v) => {
+                DateTime::from_days(*v).ok_or_else(|| Error::InvalidDateTime(v.to_string()))
+            }
+            v => Err(Error::InvalidDateTime(format!("{:?}", v))),
+        }
+    }
+}
+
+/// Exact decimal text of an unscaled integer with `scale` digits after the
+/// decimal point.
+pub(crate) fn decimal_text(value: i128, scale: u8) -> String {
+    let scale = scale as usize;
+    let digits = format!("{:0>1$}", value.unsigned_abs(), scale + 1);
+    let (int, frac) = digits.split_at(digits.len() - scale);
+    let sign = if value < 0 { "-" } else { "" };
+    match scale {
+        0 => format!("{}{}", sign, int),
+        _ => format!("{}{}.{}", sign, int, frac),
+    }
+}
+
+fn fixed<const N: usize>(data: &[u8]) -> Option<[u8; N]> {
+    data.get(..N)?.try_into().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn guid_format() {
+        let guid = [
+            0x44, 0x0f, 0x48, 0x2b, 0x67, 0xfa, 0xd7, 0x41, 0xab, 0xe9, 0x4d, 0x2f, 0x0e, 0x2c,
+            0x90, 0x87,
+        ];
+        assert_eq!(
+            GuidFormat::Braced.format(&guid),
+            "{2B480F44-FA67-41D7-ABE9-4D2F0E2C9087}"
+        );
+        assert_eq!(
+            GuidFormat::Hyphenated.format(&guid),
+            "2b480f44-fa67-41d7-abe9-4d2f0e2c9087"
+        );
+        assert_eq!(
+            GuidFormat::Simple.format(&guid),
+            "2b480f44fa6741d7abe94d2f0e2c9087"
+        );
+    }
+
+    #[test]
+    fn split_datetime() {
+        assert_eq!(super::split_datetime(36526.0), Some((36526, 0)));
+        assert_eq!(super::split_datetime(36526.25), Some((36526, 21_600_000)));
+        // Before 1899-12-30 the fraction still counts forward from midnight.
+        assert_eq!(super::split_datetime(-1.25), Some((-1, 21_600_000)));
+        assert_eq!(super::split_datetime(-657_435.0), None);
+        assert_eq!(super::split_datetime(2_958_466.0), None);
+    }
+
+    #[test]
+    fn datetime() {
+        let dt = DateTime::from_days(36526.0).unwrap();
+        assert_eq!((dt.year, dt.month, dt.day, dt.ordinal), (2000, 1, 1, 1));
+        assert_eq!(dt.iso(), "2000-01-01T00:00:00");
+
+        // Leap day of 2000, and the time of day of a date before 1899-12-30.
+        assert_eq!(DateTime::from_days(36586.0).unwrap().ordinal, 61);
+        assert_eq!(
+            DateTime::from_days(-1.5).unwrap().iso(),
+            "1899-12-29T12:00:00"
+        );
+        assert_eq!(
+            DateTime::from_days(0.75 + 1.0 / 86_400_000.0 * 250.0)
+                .unwrap()
+                .iso(),
+            "1899-12-30T18:00:00.250"
+        );
+        assert_eq!(DateTime::from_days(3_000_000.0), None);
+    }
+
+    #[test]
+    fn decimal_text() {
+        assert_eq!(super::decimal_text(990000, 4), "99.0000");
+        assert_eq!(super::decimal_text(-5, 4), "-0.0005");
+        assert_eq!(
+            super::decimal_text(12345678901234567890123, 2),
+            "123456789012345678901.23"
+        );
+        assert_eq!(super::decimal_text(-42, 0), "-42");
+    }
+}